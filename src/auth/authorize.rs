@@ -0,0 +1,210 @@
+use crate::Scopes;
+use rand::{distributions::Alphanumeric, Rng};
+use thiserror::Error;
+use url::Url;
+
+const AUTHORIZE_URL: &str = "https://open.trovo.live/page/login.html";
+const STATE_LENGTH: usize = 32;
+
+/// Builds the url to send users to so they can authorize your app.
+///
+/// Unless one is provided, a random `state` is generated to protect against CSRF attacks. Use
+/// [`AuthorizeUrl::verify_callback`] to check it when the user is redirected back.
+#[derive(Debug, Clone)]
+pub struct AuthorizeUrlBuilder {
+    client_id: String,
+    redirect_uri: String,
+    response_type: &'static str,
+    scopes: Scopes,
+    state: Option<String>,
+}
+
+impl AuthorizeUrlBuilder {
+    /// Start building an authorization url for the authorization code flow.
+    ///
+    /// Once authorized, the user is redirected with a code in the query string that can be
+    /// exchanged for an access token using [`CodeExchange`](crate::CodeExchange).
+    pub fn code(client_id: impl Into<String>, redirect_uri: impl Into<String>) -> Self {
+        Self {
+            client_id: client_id.into(),
+            redirect_uri: redirect_uri.into(),
+            response_type: "code",
+            scopes: Scopes::new(),
+            state: None,
+        }
+    }
+
+    /// Start building an authorization url for the implicit grant flow.
+    ///
+    /// Once authorized, the user is redirected with the access token in the url fragment. This
+    /// is intended for browser based apps that cannot keep a client secret.
+    ///
+    /// Trovo doesn't support PKCE, so this is also the flow to use for CLI tools and desktop
+    /// apps that can't embed a client secret.
+    pub fn implicit(client_id: impl Into<String>, redirect_uri: impl Into<String>) -> Self {
+        Self {
+            client_id: client_id.into(),
+            redirect_uri: redirect_uri.into(),
+            response_type: "token",
+            scopes: Scopes::new(),
+            state: None,
+        }
+    }
+
+    /// Set the scopes to request from the user, replacing any previously set.
+    pub fn scopes(mut self, scopes: impl Into<Scopes>) -> Self {
+        self.scopes = scopes.into();
+        self
+    }
+
+    /// Set the state that is passed back to the redirect uri, rather than generating a random
+    /// one.
+    pub fn state(mut self, state: impl Into<String>) -> Self {
+        self.state = Some(state.into());
+        self
+    }
+
+    /// Build the authorization url.
+    pub fn build(&self) -> AuthorizeUrl {
+        let state = self.state.clone().unwrap_or_else(|| {
+            rand::thread_rng()
+                .sample_iter(&Alphanumeric)
+                .take(STATE_LENGTH)
+                .map(char::from)
+                .collect()
+        });
+
+        let mut url = Url::parse(AUTHORIZE_URL).expect("authorize url is valid");
+        {
+            let mut query = url.query_pairs_mut();
+            query
+                .append_pair("client_id", &self.client_id)
+                .append_pair("response_type", self.response_type)
+                // Trovo expects the scopes separated by `+`, which is how spaces are encoded
+                .append_pair(
+                    "scope",
+                    &self
+                        .scopes
+                        .iter()
+                        .map(|scope| scope.as_str())
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                )
+                .append_pair("redirect_uri", &self.redirect_uri)
+                .append_pair("state", &state);
+        }
+
+        AuthorizeUrl {
+            url,
+            state,
+            implicit: self.response_type == "token",
+        }
+    }
+}
+
+/// An authorization url built by [`AuthorizeUrlBuilder`]
+#[derive(Debug, Clone)]
+pub struct AuthorizeUrl {
+    url: Url,
+    state: String,
+    implicit: bool,
+}
+
+impl AuthorizeUrl {
+    /// The url to send the user to
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    /// The state that will be passed back to the redirect uri
+    pub fn state(&self) -> &str {
+        &self.state
+    }
+
+    /// Verify the url the user was redirected back to, checking that the state matches.
+    ///
+    /// Returns the authorization code for the code flow, or the access token for the implicit
+    /// flow.
+    pub fn verify_callback(&self, callback_url: &str) -> Result<String, CallbackError> {
+        let url = Url::parse(callback_url)?;
+        let (value_key, params) = if self.implicit {
+            // Implicit grant returns its params in the fragment rather than the query string
+            let fragment = url.fragment().unwrap_or_default();
+            (
+                "access_token",
+                url::form_urlencoded::parse(fragment.as_bytes()),
+            )
+        } else {
+            ("code", url.query_pairs())
+        };
+
+        let mut state = None;
+        let mut value = None;
+        for (key, v) in params {
+            if key == "state" {
+                state = Some(v.into_owned());
+            } else if key == value_key {
+                value = Some(v.into_owned());
+            }
+        }
+
+        if state.as_deref() != Some(self.state.as_str()) {
+            return Err(CallbackError::StateMismatch);
+        }
+        value.ok_or(CallbackError::MissingCode)
+    }
+}
+
+/// Errors that can happen verifying an authorization callback url
+#[derive(Debug, Error)]
+pub enum CallbackError {
+    /// The callback url couldn't be parsed
+    #[error("invalid callback url: {0}")]
+    InvalidUrl(#[from] url::ParseError),
+
+    /// The state in the callback didn't match the one sent, the request may have been forged
+    #[error("callback state did not match")]
+    StateMismatch,
+
+    /// The callback didn't contain an authorization code or access token
+    #[error("callback is missing the authorization code")]
+    MissingCode,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verify_code_callback() {
+        let authorize = AuthorizeUrlBuilder::code("client", "http://localhost/callback").build();
+        assert_eq!(authorize.state().len(), STATE_LENGTH);
+
+        let callback = format!(
+            "http://localhost/callback?code=abc&state={}",
+            authorize.state()
+        );
+        assert_eq!(authorize.verify_callback(&callback).unwrap(), "abc");
+
+        let forged = "http://localhost/callback?code=abc&state=forged";
+        assert!(matches!(
+            authorize.verify_callback(forged),
+            Err(CallbackError::StateMismatch)
+        ));
+
+        let missing = format!("http://localhost/callback?state={}", authorize.state());
+        assert!(matches!(
+            authorize.verify_callback(&missing),
+            Err(CallbackError::MissingCode)
+        ));
+    }
+
+    #[test]
+    fn verify_implicit_callback() {
+        let authorize = AuthorizeUrlBuilder::implicit("client", "http://localhost/callback")
+            .state("xyz")
+            .build();
+        let callback = "http://localhost/callback#access_token=token&expires_in=100&state=xyz";
+        assert_eq!(authorize.verify_callback(callback).unwrap(), "token");
+    }
+}
@@ -0,0 +1,101 @@
+use crate::{AccessToken, AccessTokenProvider, Secret};
+use chrono::Utc;
+use std::time::Duration;
+use tokio::{select, sync::watch, task::JoinHandle, time::sleep};
+
+/// How long to wait before trying again after a refresh fails
+const RETRY_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Minimum time between refreshes, in case the provider hands out tokens that expire within the
+/// refresh margin
+const MIN_REFRESH_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Handle to a background task started by [`spawn_refresh_task`].
+///
+/// Dropping the handle stops the task.
+#[derive(Debug)]
+pub struct RefreshTask {
+    token: watch::Receiver<Secret>,
+    handle: JoinHandle<()>,
+}
+
+impl RefreshTask {
+    /// Returns a receiver that always holds the current access token.
+    pub fn token(&self) -> watch::Receiver<Secret> {
+        self.token.clone()
+    }
+}
+
+impl Drop for RefreshTask {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+/// Spawn a task that refreshes the provider's token `margin` before it expires, publishing each
+/// new token to a watch channel. Useful for long running bots that hold onto the token, such as
+/// for chat connections.
+///
+/// Only tokens returned as [`AccessToken::ExpiresAt`] are refreshed ahead of time. If the
+/// initial token needs refreshing this is done before the task is spawned, returning any error.
+pub async fn spawn_refresh_task<P>(provider: P, margin: Duration) -> Result<RefreshTask, P::Error>
+where
+    P: 'static + AccessTokenProvider + Send + Sync,
+{
+    let token = match provider.access_token() {
+        AccessToken::Token(token) | AccessToken::ExpiresAt(token, _) => token,
+        AccessToken::NeedsRefresh => provider.refresh_token().await?,
+    };
+    let (sender, receiver) = watch::channel(Secret::new(token));
+
+    let handle = tokio::spawn(async move {
+        loop {
+            let expires_at = match provider.access_token() {
+                AccessToken::ExpiresAt(_, expires_at) => expires_at,
+                AccessToken::Token(_) => {
+                    trace!("token has no expiry, nothing to refresh");
+                    sender.closed().await;
+                    break;
+                }
+                AccessToken::NeedsRefresh => Utc::now(),
+            };
+
+            let wait = (expires_at - Utc::now())
+                .to_std()
+                .unwrap_or_default()
+                .checked_sub(margin)
+                .unwrap_or_default()
+                .max(MIN_REFRESH_INTERVAL);
+            select! {
+                _ = sleep(wait) => {}
+                _ = sender.closed() => break,
+            }
+
+            let failed = match provider.refresh_token().await {
+                Ok(token) => {
+                    debug!("refreshed token in background");
+                    if sender.send(Secret::new(token)).is_err() {
+                        break;
+                    }
+                    false
+                }
+                Err(err) => {
+                    warn!(%err, "failed to refresh token in background, retrying");
+                    true
+                }
+            };
+            if failed {
+                select! {
+                    _ = sleep(RETRY_INTERVAL) => {}
+                    _ = sender.closed() => break,
+                }
+            }
+        }
+        trace!("refresh task exited");
+    });
+
+    Ok(RefreshTask {
+        token: receiver,
+        handle,
+    })
+}
@@ -0,0 +1,217 @@
+use crate::{AccessToken, AccessTokenProvider, ClientIdProvider, Scopes, Secret};
+use chrono::{DateTime, Utc};
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    RwLock,
+};
+use tokio::sync::Mutex;
+
+/// Wraps an access token provider, remembering the token returned by
+/// [`refresh_token`](AccessTokenProvider::refresh_token) and returning it from
+/// [`access_token`](AccessTokenProvider::access_token) until the next refresh.
+///
+/// If the wrapped provider reports an expiry for the refreshed token, the expiry is kept with
+/// the cached token so the client still refreshes it before it expires.
+///
+/// This allows providers that can't store tokens themselves, such as
+/// [`AccessTokenOnly`](crate::AccessTokenOnly) style providers, to be refreshed without
+/// re-refreshing on every request.
+///
+/// Concurrent refreshes are deduplicated, only one refresh of the wrapped provider runs at a
+/// time and any callers that were waiting on it receive its token rather than refreshing again.
+#[derive(Debug)]
+pub struct CachedTokenProvider<P> {
+    inner: P,
+    token: RwLock<Option<(Secret, Option<DateTime<Utc>>)>>,
+
+    /// Incremented every time a refresh succeeds
+    generation: AtomicU64,
+    refreshing: Mutex<()>,
+}
+
+impl<P> CachedTokenProvider<P> {
+    /// Wrap the given provider
+    pub fn new(inner: P) -> Self {
+        Self {
+            inner,
+            token: RwLock::new(None),
+            generation: AtomicU64::new(0),
+            refreshing: Mutex::new(()),
+        }
+    }
+
+    /// Returns the wrapped provider
+    pub fn inner(&self) -> &P {
+        &self.inner
+    }
+
+    /// Unwraps the wrapped provider, discarding any cached token
+    pub fn into_inner(self) -> P {
+        self.inner
+    }
+}
+
+impl<P> ClientIdProvider for CachedTokenProvider<P>
+where
+    P: ClientIdProvider,
+{
+    fn client_id(&self) -> &str {
+        self.inner.client_id()
+    }
+}
+
+#[async_trait::async_trait]
+impl<P> AccessTokenProvider for CachedTokenProvider<P>
+where
+    P: AccessTokenProvider + Send + Sync,
+{
+    type Error = P::Error;
+
+    fn access_token(&self) -> AccessToken {
+        let inner = self.inner.access_token();
+        match (&*self.token.read().unwrap(), inner) {
+            (None, inner) => inner,
+            // The wrapped provider stored the refreshed token itself and knows best
+            (Some((token, _)), inner) if inner_token(&inner) == Some(token.expose_secret()) => {
+                inner
+            }
+            (Some((token, Some(expires_at))), _) => {
+                AccessToken::ExpiresAt(token.expose_secret().to_string(), *expires_at)
+            }
+            (Some((token, None)), _) => AccessToken::Token(token.expose_secret().to_string()),
+        }
+    }
+
+    fn scopes(&self) -> Option<Scopes> {
+        self.inner.scopes()
+    }
+
+    async fn refresh_token(&self) -> Result<String, Self::Error> {
+        let generation = self.generation.load(Ordering::SeqCst);
+        let _guard = self.refreshing.lock().await;
+
+        // Someone else refreshed the token while we were waiting, so use theirs
+        if self.generation.load(Ordering::SeqCst) != generation {
+            if let Some((token, _)) = &*self.token.read().unwrap() {
+                return Ok(token.expose_secret().to_string());
+            }
+        }
+
+        let token = self.inner.refresh_token().await?;
+        let expires_at = match self.inner.access_token() {
+            AccessToken::ExpiresAt(inner, expires_at) if inner == token => Some(expires_at),
+            _ => None,
+        };
+        *self.token.write().unwrap() = Some((Secret::new(token.clone()), expires_at));
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        Ok(token)
+    }
+}
+
+fn inner_token(token: &AccessToken) -> Option<&str> {
+    match token {
+        AccessToken::Token(token) | AccessToken::ExpiresAt(token, _) => Some(token),
+        AccessToken::NeedsRefresh => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{convert::Infallible, time::Duration};
+
+    #[derive(Debug, Default)]
+    struct CountingProvider {
+        refreshes: AtomicU64,
+    }
+
+    impl ClientIdProvider for CountingProvider {
+        fn client_id(&self) -> &str {
+            "client"
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl AccessTokenProvider for CountingProvider {
+        type Error = Infallible;
+
+        fn access_token(&self) -> AccessToken {
+            AccessToken::NeedsRefresh
+        }
+
+        async fn refresh_token(&self) -> Result<String, Self::Error> {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            let n = self.refreshes.fetch_add(1, Ordering::SeqCst) + 1;
+            Ok(n.to_string())
+        }
+    }
+
+    #[tokio::test]
+    async fn deduplicates_concurrent_refreshes() {
+        let provider = CachedTokenProvider::new(CountingProvider::default());
+        assert!(matches!(provider.access_token(), AccessToken::NeedsRefresh));
+
+        let (a, b, c) = tokio::join!(
+            provider.refresh_token(),
+            provider.refresh_token(),
+            provider.refresh_token()
+        );
+        assert_eq!(
+            (a.unwrap(), b.unwrap(), c.unwrap()),
+            ("1".into(), "1".into(), "1".into())
+        );
+        assert_eq!(provider.inner().refreshes.load(Ordering::SeqCst), 1);
+        assert!(matches!(provider.access_token(), AccessToken::Token(token) if token == "1"));
+
+        // Later refreshes still go through
+        assert_eq!(provider.refresh_token().await.unwrap(), "2");
+    }
+
+    #[derive(Debug)]
+    struct ExpiringProvider {
+        token: RwLock<Option<DateTime<Utc>>>,
+    }
+
+    impl ClientIdProvider for ExpiringProvider {
+        fn client_id(&self) -> &str {
+            "client"
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl AccessTokenProvider for ExpiringProvider {
+        type Error = Infallible;
+
+        fn access_token(&self) -> AccessToken {
+            match *self.token.read().unwrap() {
+                Some(expires_at) => AccessToken::ExpiresAt("token".into(), expires_at),
+                None => AccessToken::NeedsRefresh,
+            }
+        }
+
+        async fn refresh_token(&self) -> Result<String, Self::Error> {
+            *self.token.write().unwrap() = Some(Utc::now() + chrono::Duration::hours(1));
+            Ok("token".into())
+        }
+    }
+
+    #[tokio::test]
+    async fn keeps_the_expiry_of_refreshed_tokens() {
+        let provider = CachedTokenProvider::new(ExpiringProvider {
+            token: RwLock::new(None),
+        });
+        provider.refresh_token().await.unwrap();
+        let expires_at = provider.inner().token.read().unwrap().unwrap();
+        assert!(matches!(
+            provider.access_token(),
+            AccessToken::ExpiresAt(token, at) if token == "token" && at == expires_at
+        ));
+
+        // The wrapped provider forgetting the token doesn't hide that it expires
+        *provider.inner().token.write().unwrap() = None;
+        assert!(matches!(
+            provider.access_token(),
+            AccessToken::ExpiresAt(token, at) if token == "token" && at == expires_at
+        ));
+    }
+}
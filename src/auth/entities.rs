@@ -0,0 +1,139 @@
+use crate::{Scopes, Secret};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+
+/// Payload for the exchange token api
+#[derive(Debug, Serialize)]
+pub struct ExchangeTokenPayload {
+    /// Client secret of the application
+    pub client_secret: Secret,
+
+    /// Grant type, always `authorization_code` for this endpoint
+    pub grant_type: String,
+
+    /// Authorization code obtained from the redirect
+    pub code: Secret,
+
+    /// Redirect uri used when obtaining the authorization code
+    pub redirect_uri: String,
+}
+
+/// Payload for the refresh token api
+#[derive(Debug, Serialize)]
+pub struct RefreshTokenPayload {
+    /// Client secret of the application
+    pub client_secret: Secret,
+
+    /// Grant type, always `refresh_token` for this endpoint
+    pub grant_type: String,
+
+    /// Refresh token obtained from a previous exchange or refresh
+    pub refresh_token: Secret,
+}
+
+/// Payload for the revoke token api
+#[derive(Debug, Serialize)]
+pub struct RevokeTokenPayload {
+    /// Access token to revoke
+    pub access_token: Secret,
+}
+
+/// Response for the exchange token & refresh token apis
+#[derive(Debug, Deserialize)]
+#[non_exhaustive]
+pub struct TokenResponse {
+    /// Access token used to make authenticated requests
+    pub access_token: Secret,
+
+    /// Type of the access token, this is always `OAuth`
+    pub token_type: String,
+
+    /// Number of seconds until the access token expires
+    pub expires_in: u64,
+
+    /// Refresh token used to obtain a new access token once it has expired
+    pub refresh_token: Secret,
+
+    /// Scopes granted to the access token. Empty if Trovo didn't include them in the response.
+    #[serde(default)]
+    pub scope: Scopes,
+}
+
+/// Information about an access token returned by the validate api
+#[derive(Debug, Deserialize)]
+pub struct TokenInfo {
+    /// Id of the user the token belongs to
+    pub uid: String,
+
+    /// Client id of the application the token was issued to
+    pub client_id: String,
+
+    /// Display name of the user the token belongs to
+    pub nick_name: String,
+
+    /// Scopes the token has been granted
+    pub scopes: Scopes,
+
+    /// When the token expires
+    #[serde(with = "serde_with::chrono::datetime_utc_ts_seconds_from_any")]
+    pub expire_ts: DateTime<Utc>,
+}
+
+/// An access token along with the refresh token needed to renew it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefreshableToken {
+    /// Access token used to make authenticated requests
+    pub access_token: Secret,
+
+    /// Refresh token used to obtain a new access token once it has expired
+    pub refresh_token: Secret,
+
+    /// When the access token expires, if known
+    pub expires_at: Option<DateTime<Utc>>,
+
+    /// Scopes granted to the access token, empty if not known
+    #[serde(default)]
+    pub scopes: Scopes,
+}
+
+impl From<TokenResponse> for RefreshableToken {
+    fn from(response: TokenResponse) -> Self {
+        Self {
+            access_token: response.access_token,
+            refresh_token: response.refresh_token,
+            // Out of range expiries are treated as unknown rather than overflowing
+            expires_at: i64::try_from(response.expires_in)
+                .ok()
+                .and_then(Duration::try_seconds)
+                .and_then(|expires_in| Utc::now().checked_add_signed(expires_in)),
+            scopes: response.scope,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(expires_in: u64) -> TokenResponse {
+        TokenResponse {
+            access_token: Secret::new("access"),
+            token_type: "OAuth".to_string(),
+            expires_in,
+            refresh_token: Secret::new("refresh"),
+            scope: Scopes::default(),
+        }
+    }
+
+    #[test]
+    fn expiry_is_computed_from_expires_in() {
+        let token = RefreshableToken::from(response(3600));
+        let expires_in = token.expires_at.unwrap() - Utc::now();
+        assert!(expires_in > Duration::seconds(3500) && expires_in <= Duration::seconds(3600));
+
+        assert!(RefreshableToken::from(response(u64::MAX))
+            .expires_at
+            .is_none());
+    }
+}
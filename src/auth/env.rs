@@ -0,0 +1,130 @@
+use crate::{
+    AccessToken, AccessTokenExpired, AccessTokenOnly, AccessTokenProvider, ClientIdProvider,
+    CodeExchange, OAuthError, RefreshableToken, RefreshingTokenProvider, Scopes,
+};
+use std::env::{self, VarError};
+use thiserror::Error;
+
+const CLIENT_ID_VAR: &str = "TROVO_CLIENT_ID";
+const ACCESS_TOKEN_VAR: &str = "TROVO_ACCESS_TOKEN";
+const REFRESH_TOKEN_VAR: &str = "TROVO_REFRESH_TOKEN";
+const CLIENT_SECRET_VAR: &str = "TROVO_CLIENT_SECRET";
+const API_URL_VAR: &str = "TROVO_API_URL";
+
+/// An access token provider configured from environment variables, useful for quick scripts
+/// and integration tests.
+///
+/// Reads `TROVO_CLIENT_ID` and `TROVO_ACCESS_TOKEN`. If both `TROVO_REFRESH_TOKEN` and
+/// `TROVO_CLIENT_SECRET` are also set then the token is refreshed with a
+/// [`RefreshingTokenProvider`], otherwise refreshing fails like [`AccessTokenOnly`]. Refresh
+/// requests are sent to `TROVO_API_URL` if set.
+#[derive(Debug)]
+pub struct EnvAuthProvider {
+    inner: EnvAuthProviderInner,
+}
+
+#[derive(Debug)]
+enum EnvAuthProviderInner {
+    AccessToken(AccessTokenOnly),
+    Refreshing(RefreshingTokenProvider),
+}
+
+impl EnvAuthProvider {
+    /// Create a new provider from the current environment variables.
+    pub fn from_env() -> Result<Self, EnvAuthError> {
+        let client_id = var(CLIENT_ID_VAR)?.ok_or(EnvAuthError::Missing(CLIENT_ID_VAR))?;
+        let access_token = var(ACCESS_TOKEN_VAR)?.ok_or(EnvAuthError::Missing(ACCESS_TOKEN_VAR))?;
+
+        let inner = match (var(REFRESH_TOKEN_VAR)?, var(CLIENT_SECRET_VAR)?) {
+            (Some(refresh_token), Some(client_secret)) => {
+                let mut exchange = CodeExchange::new(client_id, client_secret);
+                if let Some(api_url) = var(API_URL_VAR)? {
+                    exchange = exchange.with_base_url(api_url);
+                }
+                EnvAuthProviderInner::Refreshing(RefreshingTokenProvider::new(
+                    exchange,
+                    RefreshableToken {
+                        access_token: access_token.into(),
+                        refresh_token: refresh_token.into(),
+                        expires_at: None,
+                        scopes: Scopes::new(),
+                    },
+                ))
+            }
+            _ => EnvAuthProviderInner::AccessToken(AccessTokenOnly::new(client_id, access_token)),
+        };
+        Ok(Self { inner })
+    }
+
+    /// Returns true if the token can be refreshed
+    pub fn is_refreshable(&self) -> bool {
+        matches!(self.inner, EnvAuthProviderInner::Refreshing(_))
+    }
+}
+
+fn var(key: &'static str) -> Result<Option<String>, EnvAuthError> {
+    match env::var(key) {
+        Ok(value) if !value.is_empty() => Ok(Some(value)),
+        Ok(_) | Err(VarError::NotPresent) => Ok(None),
+        Err(VarError::NotUnicode(_)) => Err(EnvAuthError::NotUnicode(key)),
+    }
+}
+
+impl ClientIdProvider for EnvAuthProvider {
+    fn client_id(&self) -> &str {
+        match &self.inner {
+            EnvAuthProviderInner::AccessToken(auth) => auth.client_id(),
+            EnvAuthProviderInner::Refreshing(auth) => auth.client_id(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl AccessTokenProvider for EnvAuthProvider {
+    type Error = EnvRefreshError;
+
+    fn access_token(&self) -> AccessToken {
+        match &self.inner {
+            EnvAuthProviderInner::AccessToken(auth) => auth.access_token(),
+            EnvAuthProviderInner::Refreshing(auth) => auth.access_token(),
+        }
+    }
+
+    fn scopes(&self) -> Option<Scopes> {
+        match &self.inner {
+            EnvAuthProviderInner::AccessToken(auth) => auth.scopes(),
+            EnvAuthProviderInner::Refreshing(auth) => auth.scopes(),
+        }
+    }
+
+    async fn refresh_token(&self) -> Result<String, Self::Error> {
+        Ok(match &self.inner {
+            EnvAuthProviderInner::AccessToken(auth) => auth.refresh_token().await?,
+            EnvAuthProviderInner::Refreshing(auth) => auth.refresh_token().await?,
+        })
+    }
+}
+
+/// Errors that can happen reading the auth config from the environment
+#[derive(Debug, Error)]
+pub enum EnvAuthError {
+    /// A required environment variable wasn't set
+    #[error("missing environment variable {0}")]
+    Missing(&'static str),
+
+    /// An environment variable wasn't valid unicode
+    #[error("environment variable {0} is not valid unicode")]
+    NotUnicode(&'static str),
+}
+
+/// Errors that can happen refreshing the token of an [`EnvAuthProvider`]
+#[derive(Debug, Error)]
+pub enum EnvRefreshError {
+    /// No refresh token was configured
+    #[error(transparent)]
+    NotRefreshable(#[from] AccessTokenExpired),
+
+    /// The refresh request failed
+    #[error(transparent)]
+    Request(#[from] OAuthError),
+}
@@ -0,0 +1,273 @@
+use crate::AccessTokenExpired;
+use chrono::{DateTime, Utc};
+use std::{
+    fmt::{Debug, Display},
+    sync::Arc,
+};
+
+mod authorize;
+#[cfg(not(target_arch = "wasm32"))]
+mod background;
+mod cached;
+mod entities;
+mod env;
+mod oauth;
+#[cfg(feature = "redirect-server")]
+mod redirect_server;
+mod refreshing;
+mod registry;
+mod scope;
+mod secret;
+mod shared;
+mod storage;
+
+pub use authorize::*;
+#[cfg(not(target_arch = "wasm32"))]
+pub use background::*;
+pub use cached::*;
+pub use entities::*;
+pub use env::*;
+pub use oauth::*;
+#[cfg(feature = "redirect-server")]
+pub use redirect_server::*;
+pub use refreshing::*;
+pub use registry::*;
+pub use scope::*;
+pub use secret::*;
+pub use shared::*;
+pub use storage::*;
+
+/// A trait for an auth provider that can provide
+/// a client id
+pub trait ClientIdProvider {
+    /// Get the client id for this auth provider
+    fn client_id(&self) -> &str;
+}
+
+/// A simple client id provider that simply wraps the client id string
+///
+/// Trovo has no client credentials grant for app access tokens, public endpoints such as
+/// [`Client::users`](crate::Client::users) or [`Client::emotes`](crate::Client::emotes) are
+/// authenticated with just the client id. Use this for server side tools that never act on
+/// behalf of a user.
+#[derive(Debug, Clone)]
+pub struct ClientId(pub String);
+
+impl ClientId {
+    /// Create a new ClientId wrapper with the given string
+    pub fn new(client_id: impl Into<String>) -> Self {
+        Self(client_id.into())
+    }
+}
+
+impl ClientIdProvider for ClientId {
+    fn client_id(&self) -> &str {
+        &self.0
+    }
+}
+
+impl<T> ClientIdProvider for Arc<T>
+where
+    T: ClientIdProvider + ?Sized,
+{
+    fn client_id(&self) -> &str {
+        (**self).client_id()
+    }
+}
+
+/// Represents an access token
+///
+/// The token is redacted from `Debug` output.
+#[derive(Clone)]
+pub enum AccessToken {
+    /// Access token
+    Token(String),
+
+    /// Access token along with the time it expires.
+    ///
+    /// The client refreshes the token once it is within its refresh margin of expiring, see
+    /// [`Client::with_refresh_margin`](crate::Client::with_refresh_margin).
+    ExpiresAt(String, DateTime<Utc>),
+
+    /// Access token expired or otherwise needs refreshing
+    NeedsRefresh,
+}
+
+impl Debug for AccessToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Token(_) => f.write_str("Token([redacted])"),
+            Self::ExpiresAt(_, expires_at) => {
+                write!(f, "ExpiresAt([redacted], {:?})", expires_at)
+            }
+            Self::NeedsRefresh => f.write_str("NeedsRefresh"),
+        }
+    }
+}
+
+impl From<String> for AccessToken {
+    fn from(token: String) -> Self {
+        Self::Token(token)
+    }
+}
+
+/// A trait for an auth provider that can provide
+/// an access token.
+#[async_trait::async_trait]
+pub trait AccessTokenProvider: ClientIdProvider {
+    /// Error type used for refreshing errors
+    type Error: Display + Debug;
+
+    /// Get the access token for this auth provider.
+    ///
+    /// If the token is expired, this should return AccessToken::NeedsRefresh to indicate
+    /// that [`AccessTokenProvider::refresh_token`] should be called. If the expiry is known
+    /// ahead of time, return AccessToken::ExpiresAt so that the token can be refreshed before
+    /// requests start failing.
+    fn access_token(&self) -> AccessToken;
+
+    /// The scopes granted to the access token, if known.
+    ///
+    /// When this returns `Some`, the client checks that the token has the scopes an endpoint
+    /// requires before sending the request, failing with
+    /// [`AuthenticatedRequestError::MissingScope`](crate::AuthenticatedRequestError::MissingScope)
+    /// otherwise.
+    fn scopes(&self) -> Option<Scopes> {
+        None
+    }
+
+    /// Refresh the token.
+    ///
+    /// Implementations must remember the refreshed token so that subsequent calls to
+    /// [`AccessTokenProvider::access_token`] return it rather than triggering another refresh.
+    /// Providers that can't store the token themselves can be wrapped in a
+    /// [`CachedTokenProvider`] to do so.
+    async fn refresh_token(&self) -> Result<String, Self::Error>;
+}
+
+#[async_trait::async_trait]
+impl<T> AccessTokenProvider for Arc<T>
+where
+    T: AccessTokenProvider + Send + Sync + ?Sized,
+{
+    type Error = T::Error;
+
+    fn access_token(&self) -> AccessToken {
+        (**self).access_token()
+    }
+
+    fn scopes(&self) -> Option<Scopes> {
+        (**self).scopes()
+    }
+
+    async fn refresh_token(&self) -> Result<String, Self::Error> {
+        (**self).refresh_token().await
+    }
+}
+
+/// A simple access token provider that errors if refreshing is attempted. It is strongly advised
+/// that you use [`RefreshingTokenProvider`] or implement your own [`AccessTokenProvider`] so that
+/// you can handle refreshing.
+///
+/// If the token's expiry is known, set it with [`AccessTokenOnly::with_expires_at`] so that
+/// requests fail with [`AccessTokenExpired`] once it passes rather than being sent with a dead
+/// token.
+#[derive(Debug)]
+pub struct AccessTokenOnly {
+    client_id: String,
+    token: Secret,
+    expires_at: Option<DateTime<Utc>>,
+    scopes: Option<Scopes>,
+}
+
+impl AccessTokenOnly {
+    /// Creat a new [`AccessTokenOnly`] instance with the given client id and access token
+    pub fn new(client_id: impl Into<String>, access_token: impl Into<String>) -> Self {
+        Self {
+            client_id: client_id.into(),
+            token: Secret::new(access_token),
+            expires_at: None,
+            scopes: None,
+        }
+    }
+
+    /// Sets when the token expires, after which it is reported as needing a refresh
+    pub fn with_expires_at(mut self, expires_at: DateTime<Utc>) -> Self {
+        self.expires_at = Some(expires_at);
+        self
+    }
+
+    /// Sets the scopes granted to the token, so that requests needing other scopes fail before
+    /// being sent
+    pub fn with_scopes(mut self, scopes: impl Into<Scopes>) -> Self {
+        self.scopes = Some(scopes.into());
+        self
+    }
+
+    /// Sets the expiry and scopes from the result of validating the token with
+    /// [`validate_token`]
+    pub fn with_token_info(self, info: &TokenInfo) -> Self {
+        self.with_expires_at(info.expire_ts)
+            .with_scopes(info.scopes.clone())
+    }
+}
+
+impl ClientIdProvider for AccessTokenOnly {
+    fn client_id(&self) -> &str {
+        &self.client_id
+    }
+}
+
+#[async_trait::async_trait]
+impl AccessTokenProvider for AccessTokenOnly {
+    type Error = AccessTokenExpired;
+
+    fn access_token(&self) -> AccessToken {
+        match self.expires_at {
+            Some(expires_at) if expires_at <= Utc::now() => AccessToken::NeedsRefresh,
+            _ => AccessToken::Token(self.token.expose_secret().to_string()),
+        }
+    }
+
+    fn scopes(&self) -> Option<Scopes> {
+        self.scopes.clone()
+    }
+
+    async fn refresh_token(&self) -> Result<String, Self::Error> {
+        Err(AccessTokenExpired)
+    }
+}
+
+/// Obtain an access token from a client's AccessTokenProvider, refreshing it if needed
+#[macro_export]
+#[doc(hidden)]
+macro_rules! access_token {
+    ($client: expr, $error_type: ident) => {
+        $client
+            .access_token()
+            .await
+            .map_err($error_type::RefreshToken)?
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn access_token_only_expires() {
+        let auth = AccessTokenOnly::new("client id", "token");
+        assert!(matches!(auth.access_token(), AccessToken::Token(_)));
+        assert!(auth.scopes().is_none());
+
+        let auth = AccessTokenOnly::new("client id", "token")
+            .with_expires_at(Utc::now() + chrono::Duration::hours(1))
+            .with_scopes(Scope::ChatConnect);
+        assert!(matches!(auth.access_token(), AccessToken::Token(_)));
+        assert_eq!(auth.scopes(), Some(Scope::ChatConnect.into()));
+
+        let auth = AccessTokenOnly::new("client id", "token")
+            .with_expires_at(Utc::now() - chrono::Duration::seconds(1));
+        assert!(matches!(auth.access_token(), AccessToken::NeedsRefresh));
+    }
+}
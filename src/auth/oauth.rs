@@ -0,0 +1,215 @@
+use crate::{
+    client::DEFAULT_BASE_URL,
+    transport::{self, BuilderExt},
+    ClientIdProvider, ExchangeTokenPayload, HttpTransport, OAuthError, RefreshTokenPayload,
+    RequestError, RevokeTokenPayload, Secret, TokenInfo, TokenResponse,
+};
+use http::{header, Method};
+use std::sync::Arc;
+
+/// Validate an access token, returning the user, scopes and expiry associated with it.
+///
+/// See [`Client::validate_token`](crate::Client::validate_token) for validating the token of a
+/// client's auth provider, and [`CodeExchange::validate_token`] for using another base url.
+pub async fn validate_token(
+    http: &dyn HttpTransport,
+    client_id: &str,
+    access_token: &str,
+) -> Result<TokenInfo, RequestError> {
+    validate(http, DEFAULT_BASE_URL, client_id, access_token).await
+}
+
+async fn validate(
+    http: &dyn HttpTransport,
+    base_url: &str,
+    client_id: &str,
+    access_token: &str,
+) -> Result<TokenInfo, RequestError> {
+    let req = transport::request(Method::GET, &format!("{}/validate", base_url), client_id)
+        .header(header::AUTHORIZATION, format!("OAuth {}", access_token))
+        .empty()?;
+
+    transport::parse(transport::send(http, req).await?)
+}
+
+/// Revoke an access token, such as when a user disconnects their account from your app.
+///
+/// See [`Client::revoke_token`](crate::Client::revoke_token) for revoking the token of a client's
+/// auth provider, and [`CodeExchange::revoke_token`] for using another base url.
+pub async fn revoke_token(
+    http: &dyn HttpTransport,
+    client_id: &str,
+    access_token: impl Into<String>,
+) -> Result<(), RequestError> {
+    revoke(http, DEFAULT_BASE_URL, client_id, access_token.into()).await
+}
+
+async fn revoke(
+    http: &dyn HttpTransport,
+    base_url: &str,
+    client_id: &str,
+    access_token: String,
+) -> Result<(), RequestError> {
+    let req = transport::request(Method::POST, &format!("{}/revoke", base_url), client_id).json(
+        &RevokeTokenPayload {
+            access_token: Secret::new(access_token),
+        },
+    )?;
+
+    transport::send(http, req).await?;
+    Ok(())
+}
+
+/// Exchanges the authorization codes obtained from Trovo's OAuth flow for access tokens.
+///
+/// Trovo requires the client secret for every exchange and refresh as it has no PKCE support,
+/// so this should only be used server side. Public clients should use the implicit flow, see
+/// [`AuthorizeUrlBuilder::implicit`](crate::AuthorizeUrlBuilder::implicit).
+#[derive(Debug, Clone)]
+pub struct CodeExchange {
+    http: Arc<dyn HttpTransport>,
+    client_id: String,
+    client_secret: Secret,
+    base_url: String,
+}
+
+impl CodeExchange {
+    /// Creates a new code exchange for the given application credentials.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if a TLS backend cannot be initialized, or the resolver cannot load the system configuration.
+    pub fn new(client_id: impl Into<String>, client_secret: impl Into<String>) -> Self {
+        let builder = reqwest::Client::builder();
+        #[cfg(not(target_arch = "wasm32"))]
+        let builder = builder.timeout(std::time::Duration::from_secs(30));
+        Self::from_reqwest(builder.build().unwrap(), client_id, client_secret)
+    }
+
+    /// Creates a new code exchange using the provided reqwest client.
+    pub fn from_reqwest(
+        http: reqwest::Client,
+        client_id: impl Into<String>,
+        client_secret: impl Into<String>,
+    ) -> Self {
+        Self::with_transport(http, client_id, client_secret)
+    }
+
+    /// Creates a new code exchange that sends its requests through the given transport.
+    pub fn with_transport(
+        http: impl HttpTransport + 'static,
+        client_id: impl Into<String>,
+        client_secret: impl Into<String>,
+    ) -> Self {
+        Self {
+            http: Arc::new(http),
+            client_id: client_id.into(),
+            client_secret: Secret::new(client_secret),
+            base_url: DEFAULT_BASE_URL.to_string(),
+        }
+    }
+
+    /// Sets the base url token requests are sent to, such as to point the exchange at a mock
+    /// server in tests. Defaults to `https://open-api.trovo.live/openplatform`.
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Exchange an authorization code for an access and refresh token pair.
+    ///
+    /// `redirect_uri` must match the one used when the user was sent to authorize the app.
+    /// Fails with [`OAuthError::InvalidGrant`] if the code is invalid, expired or already used.
+    pub async fn exchange(
+        &self,
+        code: impl Into<String>,
+        redirect_uri: impl Into<String>,
+    ) -> Result<TokenResponse, OAuthError> {
+        let req = transport::request(
+            Method::POST,
+            &format!("{}/exchangetoken", self.base_url),
+            &self.client_id,
+        )
+        .json(&ExchangeTokenPayload {
+            client_secret: self.client_secret.clone(),
+            grant_type: "authorization_code".to_string(),
+            code: Secret::new(code),
+            redirect_uri: redirect_uri.into(),
+        })?;
+
+        Ok(transport::parse(transport::send(&*self.http, req).await?)?)
+    }
+
+    /// Exchange a refresh token for a new access and refresh token pair.
+    ///
+    /// Fails with [`OAuthError::InvalidGrant`] if the refresh token is no longer valid, in which
+    /// case the user needs to authorize the app again.
+    pub async fn refresh(
+        &self,
+        refresh_token: impl Into<String>,
+    ) -> Result<TokenResponse, OAuthError> {
+        let req = transport::request(
+            Method::POST,
+            &format!("{}/refreshtoken", self.base_url),
+            &self.client_id,
+        )
+        .json(&RefreshTokenPayload {
+            client_secret: self.client_secret.clone(),
+            grant_type: "refresh_token".to_string(),
+            refresh_token: Secret::new(refresh_token),
+        })?;
+
+        Ok(transport::parse(transport::send(&*self.http, req).await?)?)
+    }
+
+    /// Validate an access token like [`validate_token`], using the exchange's transport and base
+    /// url.
+    pub async fn validate_token(&self, access_token: &str) -> Result<TokenInfo, RequestError> {
+        validate(&*self.http, &self.base_url, &self.client_id, access_token).await
+    }
+
+    /// Revoke an access token like [`revoke_token`], using the exchange's transport and base url.
+    pub async fn revoke_token(&self, access_token: impl Into<String>) -> Result<(), RequestError> {
+        revoke(
+            &*self.http,
+            &self.base_url,
+            &self.client_id,
+            access_token.into(),
+        )
+        .await
+    }
+}
+
+impl ClientIdProvider for CodeExchange {
+    fn client_id(&self) -> &str {
+        &self.client_id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{HttpRequest, HttpResponse, TransportError};
+
+    #[derive(Debug)]
+    struct ExpectUri(&'static str);
+
+    #[async_trait::async_trait]
+    impl HttpTransport for ExpectUri {
+        async fn execute(&self, request: HttpRequest) -> Result<HttpResponse, TransportError> {
+            assert_eq!(request.uri(), self.0);
+            Ok(http::Response::new(b"{}".to_vec()))
+        }
+    }
+
+    #[tokio::test]
+    async fn revoking_uses_the_exchange_base_url() {
+        let exchange = CodeExchange::with_transport(
+            ExpectUri("http://localhost:8080/api/revoke"),
+            "client id",
+            "client secret",
+        )
+        .with_base_url("http://localhost:8080/api/");
+        exchange.revoke_token("token").await.unwrap();
+    }
+}
@@ -0,0 +1,154 @@
+use crate::{AuthorizeUrl, CallbackError};
+use hyper::{
+    server::conn::AddrIncoming,
+    service::{make_service_fn, service_fn},
+    Body, Request, Response, Server, StatusCode,
+};
+use std::{
+    convert::Infallible,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+use thiserror::Error;
+use tokio::sync::oneshot;
+
+const CALLBACK_PATH: &str = "/callback";
+
+type CallbackSender = Arc<Mutex<Option<oneshot::Sender<Result<String, CallbackError>>>>>;
+
+/// A local http server that captures the redirect back from Trovo's authorization page, for
+/// desktop and cli apps using the authorization code flow.
+///
+/// ```no_run
+/// # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+/// use trovo::{AuthorizeUrlBuilder, RedirectServer, Scope};
+///
+/// let server = RedirectServer::bind(8080).await?;
+/// let authorize = AuthorizeUrlBuilder::code("client id", server.redirect_uri())
+///     .scopes(Scope::ChatSendSelf)
+///     .build();
+/// println!("open {} in your browser", authorize.url());
+///
+/// let code = server.wait_for_code(&authorize).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct RedirectServer {
+    incoming: AddrIncoming,
+    redirect_uri: String,
+}
+
+impl RedirectServer {
+    /// Bind the server to the given port on localhost. Use port 0 to pick any free port.
+    pub async fn bind(port: u16) -> Result<Self, RedirectServerError> {
+        let incoming = AddrIncoming::bind(&SocketAddr::from(([127, 0, 0, 1], port)))?;
+        let redirect_uri = format!(
+            "http://localhost:{}{}",
+            incoming.local_addr().port(),
+            CALLBACK_PATH
+        );
+        Ok(Self {
+            incoming,
+            redirect_uri,
+        })
+    }
+
+    /// The redirect uri to authorize with, this must also be registered with your app.
+    pub fn redirect_uri(&self) -> &str {
+        &self.redirect_uri
+    }
+
+    /// Wait for the user to be redirected back, returning the authorization code once the
+    /// callback has been verified.
+    ///
+    /// Only the authorization code flow is supported, as the implicit flow passes the access
+    /// token in the url fragment which browsers don't send to the server.
+    pub async fn wait_for_code(
+        self,
+        authorize: &AuthorizeUrl,
+    ) -> Result<String, RedirectServerError> {
+        let (sender, receiver) = oneshot::channel();
+        let sender: CallbackSender = Arc::new(Mutex::new(Some(sender)));
+        let authorize = Arc::new(authorize.clone());
+        let redirect_uri = Arc::new(self.redirect_uri);
+
+        let make_service = make_service_fn(move |_| {
+            let sender = sender.clone();
+            let authorize = authorize.clone();
+            let redirect_uri = redirect_uri.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |req| {
+                    let response = handle_request(&req, &sender, &authorize, &redirect_uri);
+                    async move { Ok::<_, Infallible>(response) }
+                }))
+            }
+        });
+
+        let (result_sender, result_receiver) = oneshot::channel();
+        let server = Server::builder(self.incoming)
+            .serve(make_service)
+            .with_graceful_shutdown(async move {
+                if let Ok(result) = receiver.await {
+                    result_sender.send(result).ok();
+                }
+            });
+        server.await?;
+
+        let code = result_receiver
+            .await
+            .map_err(|_| RedirectServerError::Closed)??;
+        Ok(code)
+    }
+}
+
+fn handle_request(
+    req: &Request<Body>,
+    sender: &CallbackSender,
+    authorize: &AuthorizeUrl,
+    redirect_uri: &str,
+) -> Response<Body> {
+    if req.uri().path() != CALLBACK_PATH {
+        return response(StatusCode::NOT_FOUND, "Not found");
+    }
+
+    let query = req.uri().query().unwrap_or_default();
+    let result = authorize.verify_callback(&format!("{}?{}", redirect_uri, query));
+    let response = match &result {
+        Ok(_) => response(
+            StatusCode::OK,
+            "Authorization complete, you can close this window.",
+        ),
+        Err(err) => response(
+            StatusCode::BAD_REQUEST,
+            &format!("Authorization failed: {}", err),
+        ),
+    };
+
+    if let Some(sender) = sender.lock().unwrap().take() {
+        sender.send(result).ok();
+    }
+    response
+}
+
+fn response(status: StatusCode, body: &str) -> Response<Body> {
+    let mut response = Response::new(Body::from(body.to_string()));
+    *response.status_mut() = status;
+    response
+}
+
+/// Errors that can happen while waiting for the authorization redirect
+#[derive(Debug, Error)]
+pub enum RedirectServerError {
+    /// Error running the http server
+    #[error(transparent)]
+    Server(#[from] hyper::Error),
+
+    /// The redirect couldn't be verified
+    #[error(transparent)]
+    Callback(#[from] CallbackError),
+
+    /// The server stopped before the redirect was received
+    #[error("server closed before receiving the redirect")]
+    Closed,
+}
@@ -0,0 +1,158 @@
+use crate::{
+    AccessToken, AccessTokenProvider, ClientIdProvider, CodeExchange, NoTokenStorage, OAuthError,
+    RefreshableToken, Scopes, TokenStorage,
+};
+use std::{fmt, sync::RwLock};
+use tokio::sync::Mutex;
+
+type RefreshCallback = Box<dyn Fn(&RefreshableToken) + Send + Sync>;
+
+/// An access token provider that automatically refreshes its access token using the refresh
+/// token grant once it has expired.
+///
+/// Concurrent refreshes are deduplicated so that the refresh token is only used once. If a
+/// refresh is cancelled after Trovo has responded the new token is still kept, but cancelling
+/// while the request is in flight can lose it, so avoid putting tight deadlines on calls that
+/// may refresh.
+///
+/// Optionally saves refreshed tokens to a [`TokenStorage`] so they can be restored with
+/// [`RefreshingTokenProvider::load`] on the next run.
+pub struct RefreshingTokenProvider<S = NoTokenStorage> {
+    exchange: CodeExchange,
+    token: RwLock<RefreshableToken>,
+    refreshing: Mutex<()>,
+    storage: S,
+    on_token_refreshed: Option<RefreshCallback>,
+}
+
+impl<S> fmt::Debug for RefreshingTokenProvider<S>
+where
+    S: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RefreshingTokenProvider")
+            .field("exchange", &self.exchange)
+            .field("token", &self.token)
+            .field("storage", &self.storage)
+            .finish_non_exhaustive()
+    }
+}
+
+impl RefreshingTokenProvider {
+    /// Create a new provider that refreshes the given token via `exchange` when needed.
+    pub fn new(exchange: CodeExchange, token: impl Into<RefreshableToken>) -> Self {
+        Self::with_storage(exchange, token, NoTokenStorage)
+    }
+}
+
+impl<S> RefreshingTokenProvider<S>
+where
+    S: TokenStorage,
+{
+    /// Create a new provider that refreshes the given token via `exchange` when needed, saving
+    /// refreshed tokens to `storage`.
+    ///
+    /// Note that the given token is not saved until it is refreshed, call
+    /// [`TokenStorage::save`] yourself if you need it persisted straight away.
+    pub fn with_storage(
+        exchange: CodeExchange,
+        token: impl Into<RefreshableToken>,
+        storage: S,
+    ) -> Self {
+        Self {
+            exchange,
+            token: RwLock::new(token.into()),
+            refreshing: Mutex::new(()),
+            storage,
+            on_token_refreshed: None,
+        }
+    }
+
+    /// Create a new provider from the token saved in `storage`.
+    ///
+    /// Returns None if no token has been saved yet.
+    pub async fn load(exchange: CodeExchange, storage: S) -> Result<Option<Self>, S::Error> {
+        let token = storage.load().await?;
+        Ok(token.map(|token| Self::with_storage(exchange, token, storage)))
+    }
+
+    /// Register a callback that is called with the new token whenever a refresh succeeds, such
+    /// as to persist it or update your UI. Replaces any previously registered callback.
+    pub fn on_token_refreshed(
+        mut self,
+        callback: impl Fn(&RefreshableToken) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_token_refreshed = Some(Box::new(callback));
+        self
+    }
+
+    /// Returns a copy of the current token, useful for persisting it between runs.
+    pub fn token(&self) -> RefreshableToken {
+        self.token.read().unwrap().clone()
+    }
+
+    /// Returns the storage tokens are saved to.
+    pub fn storage(&self) -> &S {
+        &self.storage
+    }
+}
+
+impl<S> ClientIdProvider for RefreshingTokenProvider<S> {
+    fn client_id(&self) -> &str {
+        self.exchange.client_id()
+    }
+}
+
+#[async_trait::async_trait]
+impl<S> AccessTokenProvider for RefreshingTokenProvider<S>
+where
+    S: TokenStorage,
+{
+    type Error = OAuthError;
+
+    fn access_token(&self) -> AccessToken {
+        let token = self.token.read().unwrap();
+        match token.expires_at {
+            Some(expires_at) => {
+                AccessToken::ExpiresAt(token.access_token.expose_secret().to_string(), expires_at)
+            }
+            None => AccessToken::Token(token.access_token.expose_secret().to_string()),
+        }
+    }
+
+    fn scopes(&self) -> Option<Scopes> {
+        let scopes = &self.token.read().unwrap().scopes;
+        if scopes.is_empty() {
+            None
+        } else {
+            Some(scopes.clone())
+        }
+    }
+
+    async fn refresh_token(&self) -> Result<String, Self::Error> {
+        let refresh_token = self.token.read().unwrap().refresh_token.clone();
+        let _guard = self.refreshing.lock().await;
+
+        // Someone else refreshed the token while we were waiting, so use theirs
+        {
+            let token = self.token.read().unwrap();
+            if token.refresh_token != refresh_token {
+                return Ok(token.access_token.expose_secret().to_string());
+            }
+        }
+
+        let token =
+            RefreshableToken::from(self.exchange.refresh(refresh_token.expose_secret()).await?);
+        // Refresh tokens can only be used once, so keep the new token before anything else is
+        // awaited in case this future is dropped
+        *self.token.write().unwrap() = token.clone();
+        if let Some(callback) = &self.on_token_refreshed {
+            callback(&token);
+        }
+        if let Err(err) = self.storage.save(&token).await {
+            // The refreshed token is still usable, so just warn rather than failing the refresh
+            warn!(%err, "failed to save refreshed token");
+        }
+        Ok(token.access_token.expose_secret().to_string())
+    }
+}
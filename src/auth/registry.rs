@@ -0,0 +1,61 @@
+use crate::{Client, SharedAuth};
+use std::{collections::HashMap, sync::RwLock};
+
+/// A registry of auth providers for multiple accounts, keyed by an id of your choosing such as
+/// the user or channel id.
+///
+/// Each account's provider is shared between every client created for it, so refreshes are
+/// handled in one place rather than per client. Pair with providers that deduplicate refreshes
+/// such as [`RefreshingTokenProvider`](crate::RefreshingTokenProvider) or
+/// [`CachedTokenProvider`](crate::CachedTokenProvider).
+#[derive(Debug)]
+pub struct AuthRegistry<P> {
+    providers: RwLock<HashMap<String, SharedAuth<P>>>,
+}
+
+impl<P> Default for AuthRegistry<P> {
+    fn default() -> Self {
+        Self {
+            providers: Default::default(),
+        }
+    }
+}
+
+impl<P> AuthRegistry<P> {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register the provider for the given account, returning the previously registered
+    /// provider if there was one.
+    pub fn insert(&self, id: impl Into<String>, provider: P) -> Option<SharedAuth<P>> {
+        self.providers
+            .write()
+            .unwrap()
+            .insert(id.into(), SharedAuth::new(provider))
+    }
+
+    /// Remove the provider for the given account
+    pub fn remove(&self, id: &str) -> Option<SharedAuth<P>> {
+        self.providers.write().unwrap().remove(id)
+    }
+
+    /// Get the provider for the given account
+    pub fn get(&self, id: &str) -> Option<SharedAuth<P>> {
+        self.providers.read().unwrap().get(id).cloned()
+    }
+
+    /// Ids of all the registered accounts
+    pub fn ids(&self) -> Vec<String> {
+        self.providers.read().unwrap().keys().cloned().collect()
+    }
+
+    /// Create a client that authenticates as the given account, sharing the connection pool and
+    /// configuration of `client`.
+    ///
+    /// Returns None if no provider is registered for the account.
+    pub fn client<A>(&self, client: &Client<A>, id: &str) -> Option<Client<SharedAuth<P>>> {
+        Some(client.as_user(self.get(id)?))
+    }
+}
@@ -0,0 +1,235 @@
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::{
+    collections::BTreeSet,
+    fmt::{self, Display},
+    iter::FromIterator,
+    str::FromStr,
+};
+use thiserror::Error;
+
+/// Permission that an application can request from a user
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Scope {
+    /// View your email address and user profiles
+    UserDetailsSelf,
+
+    /// View your channel details, including stream key
+    ChannelDetailsSelf,
+
+    /// Update your channel settings
+    ChannelUpdateSelf,
+
+    /// Get your subscribers list
+    ChannelSubscriptions,
+
+    /// Send chat messages as you
+    ChatSendSelf,
+
+    /// Allow others to send chat messages to your channel
+    SendToMyChannel,
+
+    /// Perform chat commands and delete chat messages as you
+    ManageMessages,
+
+    /// Connect to chat as you
+    ChatConnect,
+}
+
+impl Scope {
+    /// Name of the scope as used by the Trovo api
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::UserDetailsSelf => "user_details_self",
+            Self::ChannelDetailsSelf => "channel_details_self",
+            Self::ChannelUpdateSelf => "channel_update_self",
+            Self::ChannelSubscriptions => "channel_subscriptions",
+            Self::ChatSendSelf => "chat_send_self",
+            Self::SendToMyChannel => "send_to_my_channel",
+            Self::ManageMessages => "manage_messages",
+            Self::ChatConnect => "chat_connect",
+        }
+    }
+}
+
+impl Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Error returned when parsing a scope name that isn't known
+#[derive(Debug, Error)]
+#[error("unknown scope: {0}")]
+pub struct UnknownScope(pub String);
+
+impl FromStr for Scope {
+    type Err = UnknownScope;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "user_details_self" => Self::UserDetailsSelf,
+            "channel_details_self" => Self::ChannelDetailsSelf,
+            "channel_update_self" => Self::ChannelUpdateSelf,
+            "channel_subscriptions" => Self::ChannelSubscriptions,
+            "chat_send_self" => Self::ChatSendSelf,
+            "send_to_my_channel" => Self::SendToMyChannel,
+            "manage_messages" => Self::ManageMessages,
+            "chat_connect" => Self::ChatConnect,
+            _ => return Err(UnknownScope(s.to_string())),
+        })
+    }
+}
+
+impl Serialize for Scope {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for Scope {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(de::Error::custom)
+    }
+}
+
+/// A set of [`Scope`]s
+///
+/// Formats as the scope names joined with `+`, as expected by Trovo's authorization urls.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Scopes(BTreeSet<Scope>);
+
+impl Scopes {
+    /// Create an empty set of scopes
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a scope to the set
+    pub fn insert(&mut self, scope: Scope) {
+        self.0.insert(scope);
+    }
+
+    /// Add a scope to the set, returning the set for chaining
+    pub fn with(mut self, scope: Scope) -> Self {
+        self.insert(scope);
+        self
+    }
+
+    /// Returns true if the set contains the given scope
+    pub fn contains(&self, scope: Scope) -> bool {
+        self.0.contains(&scope)
+    }
+
+    /// Returns true if every scope in `other` is also in this set
+    pub fn contains_all(&self, other: &Scopes) -> bool {
+        self.0.is_superset(&other.0)
+    }
+
+    /// Returns true if there are no scopes in the set
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Iterate over the scopes in the set
+    pub fn iter(&self) -> impl Iterator<Item = Scope> + '_ {
+        self.0.iter().copied()
+    }
+}
+
+impl FromIterator<Scope> for Scopes {
+    fn from_iter<T: IntoIterator<Item = Scope>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl From<Scope> for Scopes {
+    fn from(scope: Scope) -> Self {
+        Self::new().with(scope)
+    }
+}
+
+impl Display for Scopes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, scope) in self.iter().enumerate() {
+            if i > 0 {
+                f.write_str("+")?;
+            }
+            scope.fmt(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl Serialize for Scopes {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ScopeNames {
+    List(Vec<String>),
+    Joined(String),
+}
+
+impl<'de> Deserialize<'de> for Scopes {
+    /// Deserialises a list of scope names, or a single string of names separated by `+` or
+    /// spaces, skipping any that aren't known.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let names = match ScopeNames::deserialize(deserializer)? {
+            ScopeNames::List(names) => names,
+            ScopeNames::Joined(names) => names
+                .split(&['+', ' '][..])
+                .filter(|name| !name.is_empty())
+                .map(str::to_string)
+                .collect(),
+        };
+        Ok(names
+            .iter()
+            .filter_map(|name| match name.parse() {
+                Ok(scope) => Some(scope),
+                Err(err) => {
+                    debug!(%err, "ignoring unknown scope");
+                    None
+                }
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_scopes() {
+        let scopes = Scopes::new()
+            .with(Scope::ChatSendSelf)
+            .with(Scope::UserDetailsSelf);
+        assert_eq!(scopes.to_string(), "user_details_self+chat_send_self");
+        assert_eq!(Scopes::new().to_string(), "");
+    }
+
+    #[test]
+    fn deserialize_skips_unknown() {
+        let scopes: Scopes =
+            serde_json::from_str(r#"["chat_connect", "some_new_scope", "manage_messages"]"#)
+                .unwrap();
+        assert_eq!(
+            scopes,
+            vec![Scope::ChatConnect, Scope::ManageMessages]
+                .into_iter()
+                .collect()
+        );
+
+        let scopes: Scopes = serde_json::from_str(r#""chat_connect+manage_messages""#).unwrap();
+        assert_eq!(
+            scopes,
+            vec![Scope::ChatConnect, Scope::ManageMessages]
+                .into_iter()
+                .collect()
+        );
+    }
+}
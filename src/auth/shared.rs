@@ -0,0 +1,79 @@
+use crate::{AccessToken, AccessTokenProvider, ClientIdProvider, Scopes};
+use std::{fmt, ops::Deref, sync::Arc};
+
+/// A cheaply cloneable handle to an auth provider, so one provider can be shared between
+/// clients, chat connections and tasks.
+///
+/// The providers in this crate use interior mutability for refreshing, so no extra locking is
+/// needed around them, every clone refreshes and sees the same token.
+///
+/// ```no_run
+/// use trovo::{AccessTokenOnly, Client, SharedAuth};
+///
+/// let auth = SharedAuth::new(AccessTokenOnly::new("client id", "access token"));
+/// let client = Client::new(auth.clone());
+/// let other_client = Client::new(auth);
+/// ```
+pub struct SharedAuth<T: ?Sized>(Arc<T>);
+
+impl<T> SharedAuth<T> {
+    /// Wrap the given provider so it can be shared
+    pub fn new(provider: T) -> Self {
+        Self(Arc::new(provider))
+    }
+}
+
+impl<T: ?Sized> Clone for SharedAuth<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for SharedAuth<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("SharedAuth").field(&self.0).finish()
+    }
+}
+
+impl<T: ?Sized> Deref for SharedAuth<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T: ?Sized> From<Arc<T>> for SharedAuth<T> {
+    fn from(provider: Arc<T>) -> Self {
+        Self(provider)
+    }
+}
+
+impl<T> ClientIdProvider for SharedAuth<T>
+where
+    T: ClientIdProvider + ?Sized,
+{
+    fn client_id(&self) -> &str {
+        self.0.client_id()
+    }
+}
+
+#[async_trait::async_trait]
+impl<T> AccessTokenProvider for SharedAuth<T>
+where
+    T: AccessTokenProvider + Send + Sync + ?Sized,
+{
+    type Error = T::Error;
+
+    fn access_token(&self) -> AccessToken {
+        self.0.access_token()
+    }
+
+    fn scopes(&self) -> Option<Scopes> {
+        self.0.scopes()
+    }
+
+    async fn refresh_token(&self) -> Result<String, Self::Error> {
+        self.0.refresh_token().await
+    }
+}
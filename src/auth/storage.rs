@@ -0,0 +1,92 @@
+use crate::RefreshableToken;
+use std::{
+    convert::Infallible,
+    fmt::{Debug, Display},
+};
+#[cfg(not(target_arch = "wasm32"))]
+use std::{io, path::PathBuf};
+#[cfg(not(target_arch = "wasm32"))]
+use thiserror::Error;
+
+/// A trait for persisting tokens so that they survive process restarts.
+///
+/// Used by [`RefreshingTokenProvider`](crate::RefreshingTokenProvider) to save tokens after they
+/// have been refreshed.
+#[async_trait::async_trait]
+pub trait TokenStorage: Send + Sync {
+    /// Error type used for loading and saving errors
+    type Error: Display + Debug;
+
+    /// Load the previously saved token, returning `None` if no token has been saved yet.
+    async fn load(&self) -> Result<Option<RefreshableToken>, Self::Error>;
+
+    /// Save the given token, replacing any previously saved token.
+    async fn save(&self, token: &RefreshableToken) -> Result<(), Self::Error>;
+}
+
+/// Token storage that doesn't persist anything.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoTokenStorage;
+
+#[async_trait::async_trait]
+impl TokenStorage for NoTokenStorage {
+    type Error = Infallible;
+
+    async fn load(&self) -> Result<Option<RefreshableToken>, Self::Error> {
+        Ok(None)
+    }
+
+    async fn save(&self, _token: &RefreshableToken) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// Token storage that saves the token as json to a file.
+///
+/// Not available on wasm.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone)]
+pub struct JsonFileTokenStorage {
+    path: PathBuf,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl JsonFileTokenStorage {
+    /// Create a new storage that reads and writes the token at the given path.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+/// Errors that can happen loading or saving tokens with [`JsonFileTokenStorage`]
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Error)]
+pub enum JsonFileTokenStorageError {
+    /// Error reading or writing the file
+    #[error(transparent)]
+    Io(#[from] io::Error),
+
+    /// Error serialising or deserialising the token
+    #[error(transparent)]
+    Serde(#[from] serde_json::Error),
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[async_trait::async_trait]
+impl TokenStorage for JsonFileTokenStorage {
+    type Error = JsonFileTokenStorageError;
+
+    async fn load(&self) -> Result<Option<RefreshableToken>, Self::Error> {
+        match tokio::fs::read(&self.path).await {
+            Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    async fn save(&self, token: &RefreshableToken) -> Result<(), Self::Error> {
+        let bytes = serde_json::to_vec_pretty(token)?;
+        tokio::fs::write(&self.path, bytes).await?;
+        Ok(())
+    }
+}
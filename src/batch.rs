@@ -0,0 +1,195 @@
+use crate::{Client, ClientIdProvider, ErrorStatus, RequestError, User};
+use futures::{stream, Future, StreamExt};
+use std::collections::HashMap;
+
+const DEFAULT_CONCURRENCY: usize = 4;
+
+/// Most usernames the get users api accepts in one request
+const USERS_PER_REQUEST: usize = 100;
+
+impl<A> Client<A> {
+    /// Run many api calls concurrently, returning their results in the same order.
+    ///
+    /// At most 4 calls run at once by default, change it with [`Batch::concurrency`]. Calls still
+    /// go through the client's rate limits, if it has any.
+    ///
+    /// ```no_run
+    /// # async fn run(client: trovo::Client<trovo::ClientId>, channel_ids: Vec<String>) {
+    /// let channels = client
+    ///     .batch(channel_ids.into_iter().map(|id| client.channel_by_id(id)))
+    ///     .concurrency(8)
+    ///     .run()
+    ///     .await;
+    /// # }
+    /// ```
+    pub fn batch<I>(&self, requests: I) -> Batch<I::IntoIter>
+    where
+        I: IntoIterator,
+        I::Item: Future,
+    {
+        Batch {
+            requests: requests.into_iter(),
+            concurrency: DEFAULT_CONCURRENCY,
+        }
+    }
+}
+
+impl<A> Client<A>
+where
+    A: ClientIdProvider,
+{
+    /// Look up any number of users by username, returning each user or None if they don't exist,
+    /// in the same order as the usernames.
+    ///
+    /// The usernames are split into requests of up to 100, with at most 4 running at once. Unlike
+    /// [`Client::users`], a username that doesn't exist doesn't hide the other users: the
+    /// usernames in that request are looked up one by one instead.
+    pub async fn lookup_users(
+        &self,
+        usernames: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Result<Vec<Option<User>>, RequestError> {
+        let usernames: Vec<String> = usernames.into_iter().map(Into::into).collect();
+        let chunks = self
+            .batch(
+                usernames
+                    .chunks(USERS_PER_REQUEST)
+                    .map(|chunk| self.lookup_users_chunk(chunk)),
+            )
+            .run()
+            .await;
+
+        let mut found = HashMap::new();
+        for users in chunks {
+            for user in users? {
+                found.insert(user.username.to_lowercase(), user);
+            }
+        }
+        Ok(usernames
+            .iter()
+            .map(|username| found.get(&username.to_lowercase()).cloned())
+            .collect())
+    }
+
+    async fn lookup_users_chunk(&self, usernames: &[String]) -> Result<Vec<User>, RequestError> {
+        match self.users_with_meta(usernames.to_vec()).await {
+            Err(RequestError::ApiError(err)) if err.status == ErrorStatus::InvalidParameters => {
+                let users = self
+                    .batch(usernames.iter().map(|username| self.user(username.clone())))
+                    .run()
+                    .await;
+                users.into_iter().filter_map(Result::transpose).collect()
+            }
+            res => Ok(res?.data),
+        }
+    }
+}
+
+/// A batch of api calls, created with [`Client::batch`]
+#[derive(Debug)]
+#[must_use = "a batch does nothing until it is run"]
+pub struct Batch<I> {
+    requests: I,
+    concurrency: usize,
+}
+
+impl<I> Batch<I>
+where
+    I: Iterator,
+    I::Item: Future,
+{
+    /// Sets the maximum number of calls to run at once. Defaults to 4.
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Run the calls, returning their results in the order they were given
+    pub async fn run(self) -> Vec<<I::Item as Future>::Output> {
+        stream::iter(self.requests)
+            .buffered(self.concurrency)
+            .collect()
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Client, ClientId, HttpRequest, HttpResponse, HttpTransport, TransportError};
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        time::Duration,
+    };
+
+    #[tokio::test]
+    async fn batch_is_ordered_and_bounded() {
+        let client = Client::new(ClientId::new("client id"));
+        let running = AtomicUsize::new(0);
+        let max_running = AtomicUsize::new(0);
+
+        let results = client
+            .batch((0..10u64).map(|i| {
+                let running = &running;
+                let max_running = &max_running;
+                async move {
+                    let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                    max_running.fetch_max(now, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(10 - i)).await;
+                    running.fetch_sub(1, Ordering::SeqCst);
+                    i
+                }
+            }))
+            .concurrency(3)
+            .run()
+            .await;
+
+        assert_eq!(results, (0..10).collect::<Vec<_>>());
+        assert_eq!(max_running.load(Ordering::SeqCst), 3);
+    }
+
+    /// Get users api that only knows the users `a` and `b`, ignoring case like Trovo
+    #[derive(Debug)]
+    struct Users;
+
+    #[async_trait::async_trait]
+    impl HttpTransport for Users {
+        async fn execute(&self, request: HttpRequest) -> Result<HttpResponse, TransportError> {
+            let body: serde_json::Value = serde_json::from_slice(request.body()).unwrap();
+            let names: Vec<String> = body["user"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|name| name.as_str().unwrap().to_lowercase())
+                .collect();
+            let res = if names.iter().all(|name| name == "a" || name == "b") {
+                let users: Vec<_> = names
+                    .iter()
+                    .map(|name| {
+                        serde_json::json!({
+                            "user_id": name, "channel_id": name, "username": name, "nickname": name
+                        })
+                    })
+                    .collect();
+                http::Response::new(
+                    serde_json::to_vec(&serde_json::json!({ "users": users })).unwrap(),
+                )
+            } else {
+                http::Response::builder()
+                    .status(400)
+                    .body(br#"{"status": 1002, "message": "invalid parameters"}"#.to_vec())
+                    .unwrap()
+            };
+            Ok(res)
+        }
+    }
+
+    #[tokio::test]
+    async fn lookup_users_keeps_order() {
+        let client = Client::with_transport(Users, ClientId::new("client id"));
+        let users = client.lookup_users(["b", "missing", "A"]).await.unwrap();
+        let names: Vec<_> = users
+            .iter()
+            .map(|user| user.as_ref().map(|user| user.username.as_str()))
+            .collect();
+        assert_eq!(names, [Some("b"), None, Some("a")]);
+    }
+}
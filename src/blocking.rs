@@ -0,0 +1,408 @@
+//! # Blocking
+//!
+//! A blocking api client for programs that don't use async, such as simple scripts or GUI apps.
+//!
+//! Requests are run on an internal tokio runtime, so methods must not be called from within an
+//! async runtime or they will panic.
+//!
+//! ```no_run
+//! use trovo::ClientId;
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = trovo::blocking::Client::new(ClientId::new("client id"));
+//! let user = client.user("username")?;
+//! println!("found user {:#?}", user);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    AccessTokenProvider, AuthenticatedRequestError, Category, ChannelInfo, ChannelSnapshot,
+    ChannelUpdate, ClientIdProvider, Clips, ClipsQuery, EmoteChannels, EmoteFetchType, Followers,
+    FollowersQuery, LiveStatus, PastStreams, PastStreamsQuery, RequestError, StreamUrl, Subscriber,
+    Subscribers, SubscribersQuery, TokenInfo, TopChannels, TopChannelsQuery, User, UserChannelInfo,
+    Viewers, ViewersQuery,
+};
+use std::{future::Future, sync::Arc, time::Duration};
+use tokio::runtime::{Builder, Runtime};
+
+/// Blocking entrypoint for making requests to the Trovo api, mirroring [`crate::Client`].
+#[derive(Debug, Clone)]
+pub struct Client<A> {
+    inner: crate::Client<A>,
+    runtime: Arc<Runtime>,
+}
+
+impl<A> Client<A> {
+    /// Creates a new default blocking trovo client.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if a TLS backend cannot be initialized, the resolver cannot load the
+    /// system configuration, or the runtime cannot be created.
+    pub fn new(auth_provider: A) -> Self {
+        Self::from_async(crate::Client::new(auth_provider))
+    }
+
+    /// Creates a new blocking client that sends its requests with the given async client.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the runtime cannot be created.
+    pub fn from_async(client: crate::Client<A>) -> Self {
+        let runtime = Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("failed to create runtime for blocking client");
+        Self {
+            inner: client,
+            runtime: Arc::new(runtime),
+        }
+    }
+
+    /// Sets how long before an [`AccessToken::ExpiresAt`](crate::AccessToken::ExpiresAt) token
+    /// expires that it should be refreshed. Defaults to 60 seconds.
+    pub fn with_refresh_margin(mut self, refresh_margin: Duration) -> Self {
+        self.inner = self.inner.with_refresh_margin(refresh_margin);
+        self
+    }
+
+    /// Sets the base url api requests are sent to. Defaults to
+    /// `https://open-api.trovo.live/openplatform`.
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.inner = self.inner.with_base_url(base_url);
+        self
+    }
+
+    /// Returns the async client used to send requests
+    pub fn inner(&self) -> &crate::Client<A> {
+        &self.inner
+    }
+
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.runtime.block_on(future)
+    }
+}
+
+impl<A> Client<A>
+where
+    A: ClientIdProvider,
+{
+    /// Gets a list of user’s channel id, user id, and nickname, by usernames.
+    ///
+    /// See [`crate::Client::users`].
+    pub fn users(
+        &self,
+        usernames: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Result<Vec<User>, RequestError> {
+        self.block_on(self.inner.users(usernames))
+    }
+
+    /// Gets a user’s channel id, user id, and nickname, by username.
+    ///
+    /// See [`crate::Client::user`].
+    pub fn user(&self, username: impl Into<String>) -> Result<Option<User>, RequestError> {
+        self.block_on(self.inner.user(username))
+    }
+
+    /// Gets channel information for the given id
+    ///
+    /// See [`crate::Client::channel_by_id`].
+    pub fn channel_by_id(
+        &self,
+        channel_id: impl Into<String>,
+    ) -> Result<Option<ChannelInfo>, RequestError> {
+        self.block_on(self.inner.channel_by_id(channel_id))
+    }
+
+    /// Gets whether a channel is live, with its title, category and viewers.
+    ///
+    /// See [`crate::Client::is_live`].
+    pub fn is_live(
+        &self,
+        channel_id: impl Into<String>,
+    ) -> Result<Option<LiveStatus>, RequestError> {
+        self.block_on(self.inner.is_live(channel_id))
+    }
+
+    /// Gets a channel's info along with its follower, subscriber and viewer counts.
+    ///
+    /// See [`crate::Client::channel_snapshot`].
+    pub fn channel_snapshot(
+        &self,
+        channel_id: impl Into<String>,
+    ) -> Result<Option<ChannelSnapshot>, RequestError> {
+        self.block_on(self.inner.channel_snapshot(channel_id))
+    }
+
+    /// Gets channel information for the given username
+    ///
+    /// See [`crate::Client::channel_by_username`].
+    pub fn channel_by_username(
+        &self,
+        username: impl Into<String>,
+    ) -> Result<Option<ChannelInfo>, RequestError> {
+        self.block_on(self.inner.channel_by_username(username))
+    }
+
+    /// Gets a page of the channels that are live right now.
+    ///
+    /// See [`crate::Client::top_channels`].
+    pub fn top_channels(&self, query: &TopChannelsQuery) -> Result<TopChannels, RequestError> {
+        self.block_on(self.inner.top_channels(query))
+    }
+
+    /// Gets every category on Trovo.
+    ///
+    /// See [`crate::Client::categories`].
+    pub fn categories(&self) -> Result<Vec<Category>, RequestError> {
+        self.block_on(self.inner.categories())
+    }
+
+    /// Gets a category by its id.
+    ///
+    /// See [`crate::Client::category`].
+    pub fn category(&self, category_id: impl AsRef<str>) -> Result<Option<Category>, RequestError> {
+        self.block_on(self.inner.category(category_id))
+    }
+
+    /// Searches for categories by name.
+    ///
+    /// See [`crate::Client::search_categories`].
+    pub fn search_categories(
+        &self,
+        query: impl Into<String>,
+        limit: Option<u32>,
+    ) -> Result<Vec<Category>, RequestError> {
+        self.block_on(self.inner.search_categories(query, limit))
+    }
+
+    /// Gets a page of the users following a channel.
+    ///
+    /// See [`crate::Client::channel_followers`].
+    pub fn channel_followers(
+        &self,
+        channel_id: impl AsRef<str>,
+        query: &FollowersQuery,
+    ) -> Result<Followers, RequestError> {
+        self.block_on(self.inner.channel_followers(channel_id, query))
+    }
+
+    /// Gets a page of the viewers watching a live channel.
+    ///
+    /// See [`crate::Client::channel_viewers`].
+    pub fn channel_viewers(
+        &self,
+        channel_id: impl AsRef<str>,
+        query: &ViewersQuery,
+    ) -> Result<Viewers, RequestError> {
+        self.block_on(self.inner.channel_viewers(channel_id, query))
+    }
+
+    /// Gets the m3u8 playback urls of a live channel.
+    ///
+    /// See [`crate::Client::stream_urls`].
+    pub fn stream_urls(
+        &self,
+        channel_id: impl Into<String>,
+    ) -> Result<Vec<StreamUrl>, RequestError> {
+        self.block_on(self.inner.stream_urls(channel_id))
+    }
+
+    /// Gets a page of a channel's clips.
+    ///
+    /// See [`crate::Client::clips`].
+    pub fn clips(
+        &self,
+        channel_id: impl Into<String>,
+        query: &ClipsQuery,
+    ) -> Result<Clips, RequestError> {
+        self.block_on(self.inner.clips(channel_id, query))
+    }
+
+    /// Gets a page of a channel's past stream recordings.
+    ///
+    /// See [`crate::Client::past_streams`].
+    pub fn past_streams(
+        &self,
+        channel_id: impl Into<String>,
+        query: &PastStreamsQuery,
+    ) -> Result<PastStreams, RequestError> {
+        self.block_on(self.inner.past_streams(channel_id, query))
+    }
+
+    /// Gets list of emotes for each channel and global/event emotes if requested.
+    ///
+    /// See [`crate::Client::emotes`].
+    pub fn emotes(
+        &self,
+        emote_type: EmoteFetchType,
+        channel_ids: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Result<EmoteChannels, RequestError> {
+        self.block_on(self.inner.emotes(emote_type, channel_ids))
+    }
+
+    /// Get a chat token for the provided channel id
+    ///
+    /// See [`crate::Client::chat_token_for_channel`].
+    #[cfg(feature = "chat")]
+    pub fn chat_token_for_channel(
+        &self,
+        channel_id: impl AsRef<str>,
+    ) -> Result<crate::chat::ChatToken, RequestError> {
+        self.block_on(self.inner.chat_token_for_channel(channel_id))
+    }
+}
+
+impl<A> Client<A>
+where
+    A: AccessTokenProvider,
+{
+    /// Validate the auth provider's access token.
+    ///
+    /// See [`crate::Client::validate_token`].
+    pub fn validate_token(&self) -> Result<TokenInfo, AuthenticatedRequestError<A::Error>> {
+        self.block_on(self.inner.validate_token())
+    }
+
+    /// Revoke the auth provider's access token.
+    ///
+    /// See [`crate::Client::revoke_token`].
+    pub fn revoke_token(&self) -> Result<(), AuthenticatedRequestError<A::Error>> {
+        self.block_on(self.inner.revoke_token())
+    }
+
+    /// Get the authenticated user's own channel, including its stream key.
+    ///
+    /// See [`crate::Client::channel_for_user`].
+    pub fn channel_for_user(&self) -> Result<UserChannelInfo, AuthenticatedRequestError<A::Error>> {
+        self.block_on(self.inner.channel_for_user())
+    }
+
+    /// Gets a page of the users subscribed to a channel.
+    ///
+    /// See [`crate::Client::channel_subscribers`].
+    pub fn channel_subscribers(
+        &self,
+        channel_id: impl AsRef<str>,
+        query: &SubscribersQuery,
+    ) -> Result<Subscribers, AuthenticatedRequestError<A::Error>> {
+        self.block_on(self.inner.channel_subscribers(channel_id, query))
+    }
+
+    /// Find a user's subscription to a channel.
+    ///
+    /// See [`crate::Client::subscription_status`].
+    pub fn subscription_status(
+        &self,
+        channel_id: impl AsRef<str>,
+        user_id: impl AsRef<str>,
+    ) -> Result<Option<Subscriber>, AuthenticatedRequestError<A::Error>> {
+        self.block_on(self.inner.subscription_status(channel_id, user_id))
+    }
+
+    /// Update the user’s channel settings.
+    ///
+    /// See [`crate::Client::update_channel`].
+    pub fn update_channel(
+        &self,
+        channel_id: impl Into<String>,
+        update: ChannelUpdate,
+    ) -> Result<(), AuthenticatedRequestError<A::Error>> {
+        self.block_on(self.inner.update_channel(channel_id, update))
+    }
+
+    /// Change only the title of the user's channel.
+    ///
+    /// See [`crate::Client::set_title`].
+    pub fn set_title(
+        &self,
+        channel_id: impl Into<String>,
+        title: impl Into<String>,
+    ) -> Result<(), AuthenticatedRequestError<A::Error>> {
+        self.block_on(self.inner.set_title(channel_id, title))
+    }
+
+    /// Change only the category of the user's channel.
+    ///
+    /// See [`crate::Client::set_category`].
+    pub fn set_category(
+        &self,
+        channel_id: impl Into<String>,
+        category_id: impl Into<String>,
+    ) -> Result<(), AuthenticatedRequestError<A::Error>> {
+        self.block_on(self.inner.set_category(channel_id, category_id))
+    }
+
+    /// Change only the language of the user's channel.
+    ///
+    /// See [`crate::Client::set_language`].
+    pub fn set_language(
+        &self,
+        channel_id: impl Into<String>,
+        language_code: impl Into<String>,
+    ) -> Result<(), AuthenticatedRequestError<A::Error>> {
+        self.block_on(self.inner.set_language(channel_id, language_code))
+    }
+
+    /// Get a chat token for the authenticated user's channel
+    ///
+    /// See [`crate::Client::chat_token_for_user`].
+    #[cfg(feature = "chat")]
+    pub fn chat_token_for_user(
+        &self,
+    ) -> Result<crate::chat::ChatToken, AuthenticatedRequestError<A::Error>> {
+        self.block_on(self.inner.chat_token_for_user())
+    }
+
+    /// Send a chat message to the authenticated user's own channel
+    ///
+    /// See [`crate::Client::send_chat_message`].
+    #[cfg(feature = "chat")]
+    pub fn send_chat_message(
+        &self,
+        message: impl Into<String>,
+    ) -> Result<(), AuthenticatedRequestError<A::Error>> {
+        self.block_on(self.inner.send_chat_message(message))
+    }
+
+    /// Send a chat message to another channel
+    ///
+    /// See [`crate::Client::send_chat_message_to`].
+    #[cfg(feature = "chat")]
+    pub fn send_chat_message_to(
+        &self,
+        channel_id: impl Into<String>,
+        message: impl Into<String>,
+    ) -> Result<(), AuthenticatedRequestError<A::Error>> {
+        self.block_on(self.inner.send_chat_message_to(channel_id, message))
+    }
+
+    /// Perform a chat command in a channel
+    ///
+    /// See [`crate::Client::chat_command`].
+    #[cfg(feature = "chat")]
+    pub fn chat_command(
+        &self,
+        channel_id: impl Into<String>,
+        command: &crate::chat::ChatCommand,
+    ) -> Result<crate::chat::ChatCommandResponse, AuthenticatedRequestError<A::Error>> {
+        self.block_on(self.inner.chat_command(channel_id, command))
+    }
+
+    /// Delete a chat message from a channel
+    ///
+    /// See [`crate::Client::delete_chat_message`].
+    #[cfg(feature = "chat")]
+    pub fn delete_chat_message(
+        &self,
+        channel_id: impl AsRef<str>,
+        message_id: impl AsRef<str>,
+        sender_id: i64,
+    ) -> Result<(), AuthenticatedRequestError<A::Error>> {
+        self.block_on(
+            self.inner
+                .delete_chat_message(channel_id, message_id, sender_id),
+        )
+    }
+}
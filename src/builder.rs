@@ -0,0 +1,298 @@
+use crate::{
+    client::{DEFAULT_BASE_URL, DEFAULT_REFRESH_MARGIN},
+    layer::Layered,
+    rate_limit::RateLimiter,
+    Client, ClientHooks, HttpTransport, InvalidProxy, Layer, RateLimits, RetryPolicy,
+};
+use http::HeaderValue;
+use std::{sync::Arc, time::Duration};
+use url::Url;
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+const DEFAULT_USER_AGENT: &str = concat!("trovo-rs/", env!("CARGO_PKG_VERSION"));
+
+/// Builder for configuring a [`Client`], covering everything from timeouts and retries to
+/// middleware and TLS. The auth provider is passed to [`ClientBuilder::build`].
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use trovo::{ClientBuilder, ClientId, RateLimits, RetryPolicy};
+///
+/// let client = ClientBuilder::new()
+///     .api_base_url("http://localhost:8080/openplatform")
+///     .user_agent("my-bot/1.0")
+///     .timeout(Duration::from_secs(10))
+///     .retry(RetryPolicy::default())
+///     .rate_limits(RateLimits::default())
+///     .build(ClientId::new("client id"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ClientBuilder {
+    transport: Option<Arc<dyn HttpTransport>>,
+    refresh_margin: Duration,
+    base_url: String,
+    timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    proxy: Option<Url>,
+    retry: Option<RetryPolicy>,
+    rate_limits: Option<RateLimits>,
+    layers: Vec<Arc<dyn Layer>>,
+    user_agent: Option<String>,
+    hooks: Option<Arc<dyn ClientHooks>>,
+    #[cfg(feature = "chat")]
+    chat: crate::chat::ChatConfig,
+    #[cfg(all(
+        not(target_arch = "wasm32"),
+        any(feature = "rustls-tls", feature = "native-tls")
+    ))]
+    root_certificates: Vec<reqwest::Certificate>,
+}
+
+impl Default for ClientBuilder {
+    fn default() -> Self {
+        Self {
+            transport: None,
+            refresh_margin: DEFAULT_REFRESH_MARGIN,
+            base_url: DEFAULT_BASE_URL.to_string(),
+            timeout: Some(DEFAULT_TIMEOUT),
+            connect_timeout: None,
+            proxy: None,
+            retry: None,
+            rate_limits: None,
+            layers: Vec::new(),
+            user_agent: None,
+            hooks: None,
+            #[cfg(feature = "chat")]
+            chat: Default::default(),
+            #[cfg(all(
+                not(target_arch = "wasm32"),
+                any(feature = "rustls-tls", feature = "native-tls")
+            ))]
+            root_certificates: Vec::new(),
+        }
+    }
+}
+
+impl ClientBuilder {
+    /// Create a builder with the default configuration
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the base url api requests are sent to, such as to point the client at a mock server,
+    /// stub or proxy. Defaults to `https://open-api.trovo.live/openplatform`.
+    pub fn api_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Sets how long before an [`AccessToken::ExpiresAt`](crate::AccessToken::ExpiresAt) token
+    /// expires that it should be refreshed. Defaults to 60 seconds.
+    pub fn refresh_margin(mut self, refresh_margin: Duration) -> Self {
+        self.refresh_margin = refresh_margin;
+        self
+    }
+
+    /// Sets the overall timeout for each request, from sending it to reading the whole response.
+    /// Defaults to 30 seconds.
+    ///
+    /// This applies to custom transports too, and can be changed for individual calls with
+    /// [`Client::with_timeout`].
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Disables the overall request timeout, so requests can wait forever.
+    pub fn no_timeout(mut self) -> Self {
+        self.timeout = None;
+        self
+    }
+
+    /// Sets the timeout for connecting to the server. Defaults to no timeout other than the
+    /// overall request timeout.
+    ///
+    /// Only applies to the default reqwest client, not ones passed to
+    /// [`ClientBuilder::transport`] or [`ClientBuilder::reqwest_client`], and is ignored on
+    /// wasm.
+    pub fn connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.connect_timeout = Some(connect_timeout);
+        self
+    }
+
+    /// Retry requests that fail with connection errors, timeouts, rate limiting or server
+    /// errors according to the given policy. Requests aren't retried by default.
+    ///
+    /// The timeout applies to each attempt separately.
+    pub fn retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = Some(policy);
+        self
+    }
+
+    /// Queue requests so they stay within the given rate limits, rather than being rejected by
+    /// Trovo. Requests aren't rate limited by default.
+    ///
+    /// The limits are shared between clones of the client.
+    pub fn rate_limits(mut self, limits: RateLimits) -> Self {
+        self.rate_limits = Some(limits);
+        self
+    }
+
+    /// Add a middleware layer that every request passes through, after any previously added
+    /// layers. See [`Layer`].
+    ///
+    /// Layers run for each attempt at a request, after rate limiting and within the timeout.
+    pub fn with_layer(mut self, layer: impl Layer + 'static) -> Self {
+        self.layers.push(Arc::new(layer));
+        self
+    }
+
+    /// Call the given hooks for each attempt at a request, such as to record metrics. See
+    /// [`ClientHooks`].
+    pub fn hooks(mut self, hooks: impl ClientHooks + 'static) -> Self {
+        self.hooks = Some(Arc::new(hooks));
+        self
+    }
+
+    /// Send api requests and chat connections through the given proxy.
+    ///
+    /// Api requests support `http://`, `https://` and, with the `socks` feature, `socks5://`
+    /// proxies. Chat connections only support `http://` proxies, and connect directly when the
+    /// client's proxy is another kind. Without a proxy set, api requests use the system proxy
+    /// from the `HTTP_PROXY` and `HTTPS_PROXY` environment variables, and chat connections use
+    /// an `http://` proxy from `HTTPS_PROXY`.
+    ///
+    /// Like [`ClientBuilder::connect_timeout`] this isn't applied to custom transports or on
+    /// wasm.
+    ///
+    /// ```
+    /// use trovo::ClientBuilder;
+    ///
+    /// let builder = ClientBuilder::new().proxy("http://localhost:3128".parse()?)?;
+    /// assert!(ClientBuilder::new().proxy("ftp://localhost".parse()?).is_err());
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn proxy(mut self, proxy: Url) -> Result<Self, InvalidProxy> {
+        match proxy.scheme() {
+            "http" | "https" => {}
+            #[cfg(feature = "socks")]
+            "socks5" | "socks5h" => {}
+            scheme => return Err(InvalidProxy::new(format!("unsupported scheme {}", scheme))),
+        }
+        if proxy.host_str().is_none() {
+            return Err(InvalidProxy::new("missing host"));
+        }
+        self.proxy = Some(proxy);
+        Ok(self)
+    }
+
+    /// Sets the options for chat connections made by the client. See
+    /// [`ChatConfig`](crate::chat::ChatConfig).
+    #[cfg(feature = "chat")]
+    pub fn chat(mut self, config: crate::chat::ChatConfig) -> Self {
+        self.chat = config;
+        self
+    }
+
+    /// Identify your app in the `User-Agent` header sent with api requests, such as
+    /// `my-bot/1.0`. It's appended to the crate's own `trovo-rs/<version>` identifier, so that
+    /// Trovo can tell which apps and which versions of this crate requests come from.
+    pub fn user_agent(mut self, product: impl Into<String>) -> Self {
+        self.user_agent = Some(product.into());
+        self
+    }
+
+    /// Trust the given root certificate for api requests, in addition to the TLS backend's
+    /// default roots, such as for a proxy that intercepts TLS.
+    ///
+    /// Like [`ClientBuilder::connect_timeout`] this isn't applied to custom transports. Needs
+    /// the `rustls-tls` or `native-tls` feature, and isn't available on wasm, where the browser
+    /// decides which certificates to trust.
+    #[cfg(all(
+        not(target_arch = "wasm32"),
+        any(feature = "rustls-tls", feature = "native-tls")
+    ))]
+    pub fn add_root_certificate(mut self, certificate: reqwest::Certificate) -> Self {
+        self.root_certificates.push(certificate);
+        self
+    }
+
+    /// Send requests through the given transport rather than a new reqwest client.
+    pub fn transport(mut self, transport: impl HttpTransport + 'static) -> Self {
+        self.transport = Some(Arc::new(transport));
+        self
+    }
+
+    /// Send requests with the given reqwest client, to share its connection pool.
+    pub fn reqwest_client(self, http: reqwest::Client) -> Self {
+        self.transport(http)
+    }
+
+    /// Build the client with the given auth provider.
+    ///
+    /// # Panics
+    ///
+    /// If no transport was set, this method panics if a TLS backend cannot be initialized, or
+    /// the resolver cannot load the system configuration. It also panics if the user agent isn't
+    /// a valid header value.
+    pub fn build<A>(mut self, auth_provider: A) -> Client<A> {
+        let http = match self.transport.take() {
+            Some(transport) => transport,
+            None => Arc::new(self.default_reqwest_client()),
+        };
+        let http = if self.layers.is_empty() {
+            http
+        } else {
+            Arc::new(Layered {
+                layers: self.layers,
+                transport: http,
+            })
+        };
+        Client {
+            http,
+            auth_provider,
+            refresh_margin: self.refresh_margin,
+            base_url: self.base_url.into(),
+            timeout: self.timeout,
+            proxy: self.proxy.map(Arc::new),
+            user_agent: match &self.user_agent {
+                Some(product) => {
+                    HeaderValue::from_str(&format!("{} {}", DEFAULT_USER_AGENT, product)).unwrap()
+                }
+                None => HeaderValue::from_static(DEFAULT_USER_AGENT),
+            },
+            retry: self.retry,
+            rate_limiter: self
+                .rate_limits
+                .map(|limits| Arc::new(RateLimiter::new(&limits))),
+            hooks: self.hooks,
+            #[cfg(feature = "chat")]
+            chat: self.chat,
+        }
+    }
+
+    /// Create the default reqwest client
+    #[cfg(not(target_arch = "wasm32"))]
+    fn default_reqwest_client(&mut self) -> reqwest::Client {
+        let mut builder = reqwest::Client::builder();
+        #[cfg(any(feature = "rustls-tls", feature = "native-tls"))]
+        for certificate in self.root_certificates.drain(..) {
+            builder = builder.add_root_certificate(certificate);
+        }
+        if let Some(connect_timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(connect_timeout);
+        }
+        if let Some(proxy) = &self.proxy {
+            let proxy = reqwest::Proxy::all(proxy.clone()).expect("proxy checked when set");
+            builder = builder.proxy(proxy);
+        }
+        builder.build().unwrap()
+    }
+
+    /// Create the default reqwest client, which uses the browser's fetch api
+    #[cfg(target_arch = "wasm32")]
+    fn default_reqwest_client(&mut self) -> reqwest::Client {
+        reqwest::Client::new()
+    }
+}
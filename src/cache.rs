@@ -0,0 +1,223 @@
+use crate::{
+    coalesce::{RequestKey, READ_ONLY_POST_PATHS},
+    time::Instant,
+    HttpRequest, HttpResponse, Layer, Next, TransportError,
+};
+use http::{header, HeaderMap, HeaderValue, StatusCode};
+use std::{collections::HashMap, sync::Mutex, time::Duration};
+
+/// [`Layer`] that caches successful responses to read requests, for data that rarely changes
+/// such as channel info and emotes.
+///
+/// Cached responses are returned without sending a request until they're older than the ttl.
+/// After that, if the response had an `ETag` or `Last-Modified` header, the request is sent
+/// with `If-None-Match` or `If-Modified-Since` so the api can reply `304 Not Modified` and the
+/// cached response is reused. Requests are cached per url, body and access token, and only
+/// `GET` requests and `POST`s to endpoints that only read data are cached, like
+/// [`RequestCoalescer`](crate::RequestCoalescer).
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use trovo::{ClientBuilder, ClientId, ResponseCache};
+///
+/// let client = ClientBuilder::new()
+///     .with_layer(ResponseCache::new(Duration::from_secs(60)))
+///     .build(ClientId::new("client id"));
+/// ```
+#[derive(Debug)]
+pub struct ResponseCache {
+    ttl: Duration,
+    max_entries: usize,
+    paths: Vec<String>,
+    entries: Mutex<HashMap<RequestKey, Entry>>,
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Vec<u8>,
+    stored_at: Instant,
+}
+
+impl Entry {
+    fn response(&self) -> HttpResponse {
+        let mut res = http::Response::new(self.body.clone());
+        *res.status_mut() = self.status;
+        *res.headers_mut() = self.headers.clone();
+        res
+    }
+}
+
+impl ResponseCache {
+    /// Cache responses for `ttl` before checking whether they changed
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            max_entries: 1000,
+            paths: READ_ONLY_POST_PATHS
+                .iter()
+                .map(|path| path.to_string())
+                .collect(),
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Sets the maximum number of responses to keep, evicting the oldest first. Defaults to
+    /// 1000.
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries;
+        self
+    }
+
+    /// Also cache `POST` requests to endpoints whose path ends with the given path. Only add
+    /// endpoints that don't change anything.
+    pub fn with_path(mut self, path: impl Into<String>) -> Self {
+        self.paths.push(path.into());
+        self
+    }
+
+    /// Remove all cached responses
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+
+    fn store(&self, key: RequestKey, res: &HttpResponse, now: Instant) {
+        if self.max_entries == 0 {
+            return;
+        }
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= self.max_entries && !entries.contains_key(&key) {
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.stored_at)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                entries.remove(&oldest);
+            }
+        }
+        entries.insert(
+            key,
+            Entry {
+                status: res.status(),
+                headers: res.headers().clone(),
+                body: res.body().clone(),
+                stored_at: now,
+            },
+        );
+    }
+}
+
+#[async_trait::async_trait]
+impl Layer for ResponseCache {
+    async fn handle(
+        &self,
+        mut request: HttpRequest,
+        next: Next<'_>,
+    ) -> Result<HttpResponse, TransportError> {
+        let key = match RequestKey::for_read(&request, &self.paths) {
+            Some(key) => key,
+            None => return next.run(request).await,
+        };
+
+        let cached = self.entries.lock().unwrap().get(&key).cloned();
+        if let Some(entry) = &cached {
+            if entry.stored_at.elapsed() < self.ttl {
+                trace!(uri = %request.uri(), "serving cached response");
+                return Ok(entry.response());
+            }
+            add_validators(&mut request, &entry.headers);
+        }
+
+        let res = next.run(request).await?;
+        match cached {
+            Some(mut entry) if res.status() == StatusCode::NOT_MODIFIED => {
+                trace!("cached response not modified");
+                entry.stored_at = Instant::now();
+                let response = entry.response();
+                self.entries.lock().unwrap().insert(key, entry);
+                Ok(response)
+            }
+            _ => {
+                if res.status().is_success() {
+                    self.store(key, &res, Instant::now());
+                }
+                Ok(res)
+            }
+        }
+    }
+}
+
+/// Add conditional request headers for the validators of a cached response
+fn add_validators(request: &mut HttpRequest, cached: &HeaderMap) {
+    let validators: [(header::HeaderName, Option<&HeaderValue>); 2] = [
+        (header::IF_NONE_MATCH, cached.get(header::ETAG)),
+        (header::IF_MODIFIED_SINCE, cached.get(header::LAST_MODIFIED)),
+    ];
+    for (name, value) in validators {
+        if let Some(value) = value {
+            request.headers_mut().insert(name, value.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{layer::Layered, HttpTransport};
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    /// Responds with an etag, and 304 when the request has a matching `If-None-Match`
+    #[derive(Debug, Default)]
+    struct Etagged(AtomicUsize);
+
+    #[async_trait::async_trait]
+    impl HttpTransport for Etagged {
+        async fn execute(&self, request: HttpRequest) -> Result<HttpResponse, TransportError> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            let builder = http::Response::builder().header(header::ETAG, "\"v1\"");
+            let res = if request.headers().get(header::IF_NONE_MATCH)
+                == Some(&HeaderValue::from_static("\"v1\""))
+            {
+                builder.status(StatusCode::NOT_MODIFIED).body(Vec::new())
+            } else {
+                builder.body(b"channel".to_vec())
+            };
+            Ok(res.unwrap())
+        }
+    }
+
+    fn request() -> HttpRequest {
+        let mut request = http::Request::new(Vec::new());
+        *request.uri_mut() = "https://example.com/openplatform/channel".parse().unwrap();
+        request
+    }
+
+    #[tokio::test]
+    async fn caches_and_revalidates() {
+        let upstream = Arc::new(Etagged::default());
+        let transport = Layered {
+            layers: vec![Arc::new(ResponseCache::new(Duration::from_millis(50)))],
+            transport: upstream.clone(),
+        };
+
+        assert_eq!(
+            transport.execute(request()).await.unwrap().body(),
+            b"channel"
+        );
+        assert_eq!(
+            transport.execute(request()).await.unwrap().body(),
+            b"channel"
+        );
+        assert_eq!(upstream.0.load(Ordering::SeqCst), 1);
+
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        let res = transport.execute(request()).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.body(), b"channel");
+        assert_eq!(upstream.0.load(Ordering::SeqCst), 2);
+    }
+}
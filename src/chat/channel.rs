@@ -0,0 +1,221 @@
+use crate::{
+    chat::{
+        ChatCommand, ChatCommandResponse, ChatMessage, ChatMessagesForChannelError,
+        ReconnectingChatStream,
+    },
+    AccessTokenProvider, AuthenticatedRequestError, Client, ClientIdProvider,
+};
+use std::time::Duration;
+
+impl<A: Clone> Client<A> {
+    /// A handle to a channel's chat, for reading and sending messages without passing the
+    /// channel id to each call.
+    ///
+    /// ```no_run
+    /// # async fn run(client: trovo::Client<std::sync::Arc<trovo::AccessTokenOnly>>) -> Result<(), Box<dyn std::error::Error>> {
+    /// use futures::StreamExt;
+    /// use trovo::chat::ChatEvent;
+    ///
+    /// let chat = client.channel_chat("channel id");
+    /// let mut events = chat.events();
+    /// while let Some(event) = events.next().await {
+    ///     if let ChatEvent::Message(msg) = event? {
+    ///         if msg.content == "!ping" {
+    ///             chat.reply(&msg, "pong").await?;
+    ///         }
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn channel_chat(&self, channel_id: impl Into<String>) -> ChannelChat<A> {
+        ChannelChat {
+            client: self.clone(),
+            channel_id: channel_id.into(),
+        }
+    }
+}
+
+/// A channel's chat, from [`Client::channel_chat`]
+#[derive(Debug, Clone)]
+pub struct ChannelChat<A> {
+    client: Client<A>,
+    channel_id: String,
+}
+
+impl<A> ChannelChat<A> {
+    /// Id of the channel
+    pub fn channel_id(&self) -> &str {
+        &self.channel_id
+    }
+
+    /// The client used for requests
+    pub fn client(&self) -> &Client<A> {
+        &self.client
+    }
+}
+
+impl<A> ChannelChat<A>
+where
+    A: ClientIdProvider + Clone + Send + Sync + 'static,
+{
+    /// Connect to the channel's chat, see [`Client::chat_events_for_channel`]
+    pub fn events(&self) -> ReconnectingChatStream<ChatMessagesForChannelError> {
+        self.client.chat_events_for_channel(self.channel_id.clone())
+    }
+}
+
+impl<A> ChannelChat<A>
+where
+    A: AccessTokenProvider,
+{
+    /// Send a chat message to the channel, see [`Client::send_chat_message_to`]
+    pub async fn say(
+        &self,
+        message: impl Into<String>,
+    ) -> Result<(), AuthenticatedRequestError<A::Error>> {
+        self.client
+            .send_chat_message_to(self.channel_id.clone(), message)
+            .await
+    }
+
+    /// Reply to a message in the channel, mentioning its sender.
+    ///
+    /// Trovo doesn't link replies to messages, so this sends `@nick_name message`.
+    pub async fn reply(
+        &self,
+        to: &ChatMessage,
+        message: impl AsRef<str>,
+    ) -> Result<(), AuthenticatedRequestError<A::Error>> {
+        self.say(reply_text(to, message.as_ref())).await
+    }
+
+    /// Perform a chat command in the channel, see [`Client::chat_command`]
+    pub async fn command(
+        &self,
+        command: &ChatCommand,
+    ) -> Result<ChatCommandResponse, AuthenticatedRequestError<A::Error>> {
+        self.client
+            .chat_command(self.channel_id.clone(), command)
+            .await
+    }
+
+    /// Ban a user, by username, from chatting for the given duration or permanently
+    pub async fn ban(
+        &self,
+        user: impl Into<String>,
+        duration: Option<Duration>,
+    ) -> Result<ChatCommandResponse, AuthenticatedRequestError<A::Error>> {
+        self.command(&ChatCommand::Ban {
+            user: user.into(),
+            duration,
+        })
+        .await
+    }
+
+    /// Lift a user's ban, by username
+    pub async fn unban(
+        &self,
+        user: impl Into<String>,
+    ) -> Result<ChatCommandResponse, AuthenticatedRequestError<A::Error>> {
+        self.command(&ChatCommand::Unban(user.into())).await
+    }
+
+    /// Delete every message in chat
+    pub async fn clear(&self) -> Result<ChatCommandResponse, AuthenticatedRequestError<A::Error>> {
+        self.command(&ChatCommand::Clear).await
+    }
+
+    /// Only allow each user to send one message per given interval
+    pub async fn slow(
+        &self,
+        interval: Duration,
+    ) -> Result<ChatCommandResponse, AuthenticatedRequestError<A::Error>> {
+        self.command(&ChatCommand::Slow(interval)).await
+    }
+
+    /// Turn slow mode off
+    pub async fn slow_off(
+        &self,
+    ) -> Result<ChatCommandResponse, AuthenticatedRequestError<A::Error>> {
+        self.command(&ChatCommand::SlowOff).await
+    }
+
+    /// Only allow followers to chat
+    pub async fn followers_only(
+        &self,
+    ) -> Result<ChatCommandResponse, AuthenticatedRequestError<A::Error>> {
+        self.command(&ChatCommand::FollowersOnly).await
+    }
+
+    /// Allow everyone to chat again
+    pub async fn followers_only_off(
+        &self,
+    ) -> Result<ChatCommandResponse, AuthenticatedRequestError<A::Error>> {
+        self.command(&ChatCommand::FollowersOnlyOff).await
+    }
+
+    /// Delete a message from the channel's chat, see [`Client::delete_chat_message`].
+    ///
+    /// Messages without a [`sender_id`](ChatMessage::sender_id), such as platform events, can't
+    /// be deleted and fail with [`AuthenticatedRequestError::InvalidArgument`] without sending a
+    /// request.
+    pub async fn delete(
+        &self,
+        msg: &ChatMessage,
+    ) -> Result<(), AuthenticatedRequestError<A::Error>> {
+        match msg.sender_id {
+            Some(sender_id) => {
+                self.client
+                    .delete_chat_message(&self.channel_id, &msg.message_id, sender_id)
+                    .await
+            }
+            None => Err(AuthenticatedRequestError::InvalidArgument(format!(
+                "message {} has no sender id",
+                msg.message_id
+            ))),
+        }
+    }
+}
+
+fn reply_text(to: &ChatMessage, message: &str) -> String {
+    format!("@{} {}", to.nick_name, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replies_mention_the_sender() {
+        let msg: ChatMessage = serde_json::from_value(serde_json::json!({
+            "type": 0,
+            "content": "!ping",
+            "nick_name": "someone",
+            "message_id": "1",
+            "send_time": 1600000000,
+        }))
+        .unwrap();
+        assert_eq!(reply_text(&msg, "pong"), "@someone pong");
+    }
+
+    #[tokio::test]
+    async fn messages_without_senders_cant_be_deleted() {
+        let msg: ChatMessage = serde_json::from_value(serde_json::json!({
+            "type": 5007,
+            "content": "event",
+            "nick_name": "Trovo",
+            "message_id": "1",
+            "send_time": 1600000000,
+        }))
+        .unwrap();
+        let client = Client::new(std::sync::Arc::new(crate::AccessTokenOnly::new(
+            "client id",
+            "token",
+        )));
+        assert!(matches!(
+            client.channel_chat("100").delete(&msg).await,
+            Err(AuthenticatedRequestError::InvalidArgument(_))
+        ));
+    }
+}
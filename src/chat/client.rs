@@ -1,15 +1,21 @@
 use crate::{
-    access_token,
     auth::{AccessTokenProvider, ClientIdProvider},
-    chat::{ChatConnectError, ChatMessageStream, ChatToken, SendChatMessagePayload},
-    ApiError, AuthenticatedRequestError, Client, RequestError,
+    chat::{
+        default_reconnect_policy, ChatCommand, ChatCommandPayload, ChatCommandResponse, ChatConfig,
+        ChatConnectError, ChatMessageStream, ChatToken, ReconnectingChatStream,
+        SendChatMessagePayload,
+    },
+    retry::NotIdempotent,
+    transport::{self, BuilderExt},
+    AuthenticatedRequestError, Client, RequestError, Scope,
 };
-use reqwest::header;
+use http::Method;
 use std::{
     error::Error,
     fmt::{Debug, Display},
 };
 use thiserror::Error;
+use url::Url;
 
 impl<A> Client<A>
 where
@@ -20,23 +26,14 @@ where
         &self,
         channel_id: impl AsRef<str>,
     ) -> Result<ChatToken, RequestError> {
-        let res = self
-            .http
-            .get(&format!(
-                "https://open-api.trovo.live/openplatform/chat/channel-token/{}",
-                channel_id.as_ref()
-            ))
-            .header("Client-ID", self.auth_provider.client_id())
-            .send()
-            .await?;
-
-        if ApiError::can_handle_code(res.status()) {
-            let err: ApiError = res.json().await.unwrap_or_default();
-            Err(RequestError::ApiError(err))
-        } else {
-            let response = res.error_for_status()?.json().await?;
-            Ok(response)
-        }
+        let req = self
+            .request(
+                Method::GET,
+                &format!("chat/channel-token/{}", channel_id.as_ref()),
+            )
+            .empty()?;
+
+        transport::parse(self.send(req).await?)
     }
 
     /// Connect to the given channel id and receive a stream of messages.
@@ -45,40 +42,123 @@ where
         channel_id: impl AsRef<str>,
     ) -> Result<ChatMessageStream, ChatMessagesForChannelError> {
         let token = self.chat_token_for_channel(channel_id).await?;
-        let messages = ChatMessageStream::connect(token).await?;
+        let messages = ChatMessageStream::connect_with_config(token, &self.chat_config()).await?;
         Ok(messages)
     }
 }
 
+impl<A> Client<A> {
+    /// Options for chat connections, with the client's proxy if chat supports it, or otherwise
+    /// the system proxy
+    fn chat_config(&self) -> ChatConfig {
+        let mut config = self.chat.clone();
+        if config.proxy.is_none() {
+            config.proxy = match self.proxy.as_deref() {
+                Some(proxy) if proxy.scheme() == "http" => Some(proxy.clone()),
+                Some(_) => None,
+                None => system_proxy(),
+            };
+        }
+        config
+    }
+}
+
+/// The `http://` proxy set with the `HTTPS_PROXY` environment variable, if there is one
+fn system_proxy() -> Option<Url> {
+    let proxy = std::env::var("HTTPS_PROXY")
+        .or_else(|_| std::env::var("https_proxy"))
+        .ok()?;
+    Url::parse(&proxy)
+        .ok()
+        .filter(|proxy| proxy.scheme() == "http")
+}
+
+impl<A> Client<A>
+where
+    A: ClientIdProvider + Clone + Send + Sync + 'static,
+{
+    /// Connect to the given channel id and receive a stream of chat events, reconnecting
+    /// whenever the connection drops.
+    ///
+    /// ```no_run
+    /// # async fn run(client: trovo::Client<trovo::ClientId>) -> Result<(), Box<dyn std::error::Error>> {
+    /// use futures::StreamExt;
+    /// use trovo::chat::ChatEvent;
+    ///
+    /// let mut events = client.chat_events_for_channel("channel id");
+    /// while let Some(event) = events.next().await {
+    ///     match event? {
+    ///         ChatEvent::Message(msg) => println!("[{}] {}", msg.nick_name, msg.content),
+    ///         ChatEvent::Reconnected => println!("reconnected"),
+    ///         _ => {}
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn chat_events_for_channel(
+        &self,
+        channel_id: impl Into<String>,
+    ) -> ReconnectingChatStream<ChatMessagesForChannelError> {
+        let client = self.clone();
+        let channel_id = channel_id.into();
+        let config = self.chat_config();
+        ReconnectingChatStream::new(
+            move || {
+                let client = client.clone();
+                let channel_id = channel_id.clone();
+                async move { Ok(client.chat_token_for_channel(channel_id).await?) }
+            },
+            default_reconnect_policy(),
+            config,
+        )
+    }
+}
+
+impl<A> Client<A>
+where
+    A: AccessTokenProvider + Clone + Send + Sync + 'static,
+    A::Error: Send,
+{
+    /// Connect to the authenticated user's channel and receive a stream of chat events,
+    /// reconnecting whenever the connection drops. See [`Client::chat_events_for_channel`].
+    pub fn chat_events_for_user(
+        &self,
+    ) -> ReconnectingChatStream<ChatMessagesForUserError<A::Error>> {
+        let client = self.clone();
+        let config = self.chat_config();
+        ReconnectingChatStream::new(
+            move || {
+                let client = client.clone();
+                async move {
+                    client
+                        .chat_token_for_user()
+                        .await
+                        .map_err(ChatMessagesForUserError::Request)
+                }
+            },
+            default_reconnect_policy(),
+            config,
+        )
+    }
+}
+
 impl<A> Client<A>
 where
     A: AccessTokenProvider,
 {
     /// Get a chat token for the authenticated user's channel
+    ///
+    /// # Scopes
+    ///
+    /// Requires `chat_connect`
+    #[doc(alias = "chat_token_for_me")]
     pub async fn chat_token_for_user(
         &self,
     ) -> Result<ChatToken, AuthenticatedRequestError<A::Error>> {
-        let res = self
-            .http
-            .get("https://open-api.trovo.live/openplatform/chat/token")
-            .header("Client-ID", self.auth_provider.client_id())
-            .header(
-                header::AUTHORIZATION,
-                format!(
-                    "OAuth {}",
-                    access_token!(self.auth_provider, AuthenticatedRequestError)
-                ),
-            )
-            .send()
-            .await?;
-
-        if ApiError::can_handle_code(res.status()) {
-            let err: ApiError = res.json().await.unwrap_or_default();
-            Err(AuthenticatedRequestError::ApiError(err))
-        } else {
-            let response = res.error_for_status()?.json().await?;
-            Ok(response)
-        }
+        self.require_scope(Scope::ChatConnect)?;
+        let req = self.request(Method::GET, "chat/token").empty()?;
+        Ok(transport::parse(self.send_authenticated(req).await?)?)
     }
 
     /// Connect to the authenticated user's channel and receive a stream of messages.
@@ -89,52 +169,140 @@ where
             .chat_token_for_user()
             .await
             .map_err(ChatMessagesForUserError::Request)?;
-        let messages = ChatMessageStream::connect(token).await?;
+        let messages = ChatMessageStream::connect_with_config(token, &self.chat_config()).await?;
         Ok(messages)
     }
 
-    /// Send a chat message to a channel
+    /// Send a chat message to the authenticated user's own channel
     ///
-    /// # Scopes
+    /// The api doesn't return the id of the sent message. It can be found from the message
+    /// received in chat, whose [`sender_id`](crate::chat::ChatMessage::sender_id) is the user's.
     ///
-    /// ## Sending to your own channel
+    /// # Scopes
     ///
     /// Requires `chat_send_self`
+    pub async fn send_chat_message(
+        &self,
+        message: impl Into<String>,
+    ) -> Result<(), AuthenticatedRequestError<A::Error>> {
+        self.send_chat(None, message.into()).await
+    }
+
+    /// Send a chat message as the authenticated user to another channel
     ///
-    /// ## Sending to other channels
+    /// # Scopes
     ///
     /// To send a message as sender (user A) to channel (owned by user B), the application needs to
     /// get scopes `chat_send_self` of user A, and `send_to_my_channel` of user B.
-    pub async fn send_chat_message(
+    pub async fn send_chat_message_to(
         &self,
-        channel_id: Option<String>,
+        channel_id: impl Into<String>,
         message: impl Into<String>,
     ) -> Result<(), AuthenticatedRequestError<A::Error>> {
-        let res = self
-            .http
-            .post("https://open-api.trovo.live/openplatform/chat/send")
-            .header("Client-ID", self.auth_provider.client_id())
-            .header(
-                header::AUTHORIZATION,
-                format!(
-                    "OAuth {}",
-                    access_token!(self.auth_provider, AuthenticatedRequestError)
-                ),
-            )
+        self.send_chat(Some(channel_id.into()), message.into())
+            .await
+    }
+
+    async fn send_chat(
+        &self,
+        channel_id: Option<String>,
+        message: String,
+    ) -> Result<(), AuthenticatedRequestError<A::Error>> {
+        self.require_scope(Scope::ChatSendSelf)?;
+        let req = self
+            .request(Method::POST, "chat/send")
+            .extension(NotIdempotent)
             .json(&SendChatMessagePayload {
-                content: message.into(),
+                content: message,
                 channel_id,
-            })
-            .send()
-            .await?;
-
-        if ApiError::can_handle_code(res.status()) {
-            let err: ApiError = res.json().await.unwrap_or_default();
-            Err(AuthenticatedRequestError::ApiError(err))
-        } else {
-            res.error_for_status()?;
-            Ok(())
+            })?;
+
+        self.send_authenticated(req).await?;
+        Ok(())
+    }
+
+    /// Perform a chat command in a channel, such as banning a user or turning on slow mode.
+    ///
+    /// Check [`ChatCommandResponse::is_success`] for whether the command worked, the api
+    /// doesn't return an error for commands that fail. Commands with an empty username or role,
+    /// or one containing whitespace, fail with [`AuthenticatedRequestError::InvalidArgument`]
+    /// without being sent.
+    ///
+    /// ```no_run
+    /// # async fn run(client: trovo::Client<trovo::AccessTokenOnly>) -> Result<(), Box<dyn std::error::Error>> {
+    /// use std::time::Duration;
+    /// use trovo::chat::ChatCommand;
+    ///
+    /// let command = ChatCommand::Ban {
+    ///     user: "spammer".to_string(),
+    ///     duration: Some(Duration::from_secs(600)),
+    /// };
+    /// let res = client.chat_command("channel id", &command).await?;
+    /// println!("{}", res.display_msg);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Scopes
+    ///
+    /// Requires `manage_messages`
+    pub async fn chat_command(
+        &self,
+        channel_id: impl Into<String>,
+        command: &ChatCommand,
+    ) -> Result<ChatCommandResponse, AuthenticatedRequestError<A::Error>> {
+        if let Some(reason) = command.invalid_argument() {
+            return Err(AuthenticatedRequestError::InvalidArgument(reason));
         }
+        self.require_scope(Scope::ManageMessages)?;
+        let req = self
+            .request(Method::POST, "channels/command")
+            .extension(NotIdempotent)
+            .json(&ChatCommandPayload {
+                command: command.to_string(),
+                channel_id: channel_id.into(),
+            })?;
+
+        Ok(transport::parse(self.send_authenticated(req).await?)?)
+    }
+
+    /// Delete a chat message from a channel, given its [`ChatMessage::message_id`](crate::chat::ChatMessage::message_id) and
+    /// [`ChatMessage::sender_id`](crate::chat::ChatMessage::sender_id).
+    ///
+    /// ```no_run
+    /// # async fn run(client: trovo::Client<trovo::AccessTokenOnly>, message: trovo::chat::ChatMessage) -> Result<(), Box<dyn std::error::Error>> {
+    /// if let Some(sender_id) = message.sender_id {
+    ///     client
+    ///         .delete_chat_message("channel id", &message.message_id, sender_id)
+    ///         .await?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Scopes
+    ///
+    /// Requires `manage_messages` of the channel's owner
+    pub async fn delete_chat_message(
+        &self,
+        channel_id: impl AsRef<str>,
+        message_id: impl AsRef<str>,
+        sender_id: i64,
+    ) -> Result<(), AuthenticatedRequestError<A::Error>> {
+        self.require_scope(Scope::ManageMessages)?;
+        let path = format!(
+            "channels/{}/messages/{}/users/{}",
+            channel_id.as_ref(),
+            message_id.as_ref(),
+            sender_id
+        );
+        let req = self
+            .request(Method::DELETE, &path)
+            .extension(NotIdempotent)
+            .empty()?;
+
+        self.send_authenticated(req).await?;
+        Ok(())
     }
 }
 
@@ -195,3 +363,65 @@ pub enum ChatMessagesForChannelError {
     #[error(transparent)]
     ChatConnect(#[from] ChatConnectError),
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        AccessTokenOnly, ClientBuilder, ClientId, HttpRequest, HttpResponse, HttpTransport,
+        RetryPolicy, TransportError,
+    };
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+
+    /// Fails every request with a server error
+    #[derive(Debug, Default)]
+    struct Unavailable(AtomicUsize);
+
+    #[async_trait::async_trait]
+    impl HttpTransport for Unavailable {
+        async fn execute(&self, _request: HttpRequest) -> Result<HttpResponse, TransportError> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Ok(http::Response::builder()
+                .status(503)
+                .body(Vec::new())
+                .unwrap())
+        }
+    }
+
+    #[tokio::test]
+    async fn chat_messages_are_not_retried_after_server_errors() {
+        let transport = Arc::new(Unavailable::default());
+        let client = ClientBuilder::new()
+            .transport(transport.clone())
+            .retry(RetryPolicy::new(3).with_initial_backoff(Duration::from_millis(1)))
+            .build(AccessTokenOnly::new("client id", "token"));
+        assert!(client.send_chat_message("hi").await.is_err());
+        assert_eq!(transport.0.load(Ordering::SeqCst), 1);
+
+        client.chat_token_for_channel("100").await.unwrap_err();
+        assert_eq!(transport.0.load(Ordering::SeqCst), 4);
+    }
+
+    #[test]
+    fn chat_only_inherits_http_proxies() {
+        let client = ClientBuilder::new()
+            .proxy("http://localhost:3128".parse().unwrap())
+            .unwrap()
+            .build(ClientId::new("client id"));
+        assert_eq!(
+            client.chat_config().proxy.unwrap().as_str(),
+            "http://localhost:3128/"
+        );
+
+        let client = ClientBuilder::new()
+            .proxy("https://localhost:3128".parse().unwrap())
+            .unwrap()
+            .build(ClientId::new("client id"));
+        assert!(client.chat_config().proxy.is_none());
+    }
+}
@@ -0,0 +1,57 @@
+use url::Url;
+
+/// Options for chat connections, set on the client with
+/// [`ClientBuilder::chat`](crate::ClientBuilder::chat) or passed to
+/// [`ChatMessageStream::connect_with_config`](crate::chat::ChatMessageStream::connect_with_config).
+///
+/// ```no_run
+/// use trovo::{chat::ChatConfig, ClientBuilder, ClientId};
+///
+/// let client = ClientBuilder::new()
+///     .chat(ChatConfig::new().skip_history(false))
+///     .build(ClientId::new("client id"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ChatConfig {
+    pub(crate) skip_history: bool,
+    pub(crate) proxy: Option<Url>,
+}
+
+impl Default for ChatConfig {
+    fn default() -> Self {
+        Self {
+            skip_history: true,
+            proxy: None,
+        }
+    }
+}
+
+impl ChatConfig {
+    /// Create the default config
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether to skip the recent messages Trovo sends on connecting, which were sent
+    /// before the connection was made. Defaults to true, so bots don't respond to the same
+    /// messages again each time they connect.
+    ///
+    /// The history is the first batch of messages Trovo sends after authenticating.
+    /// [`ReconnectingChatStream`](crate::chat::ReconnectingChatStream)s keep the history when
+    /// reconnecting, and drop only the messages they already returned.
+    pub fn skip_history(mut self, skip_history: bool) -> Self {
+        self.skip_history = skip_history;
+        self
+    }
+
+    /// Tunnel connections through the given `http://` proxy, with the `CONNECT` method.
+    /// Credentials in the proxy url are sent using basic auth.
+    ///
+    /// Clients use the proxy set with [`ClientBuilder::proxy`](crate::ClientBuilder::proxy) if
+    /// the chat config doesn't have one and it's an `http://` proxy, or an `http://` proxy from
+    /// the `HTTPS_PROXY` environment variable if neither has one.
+    pub fn proxy(mut self, proxy: Url) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+}
@@ -1,11 +1,32 @@
+use crate::EmoteChannels;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use serde_repr::*;
-use serde_with::{serde_as, TimestampSeconds};
-use std::collections::HashMap;
+use serde_with::{serde_as, DisplayFromStr, PickFirst, TimestampSeconds};
+use std::{
+    collections::HashMap,
+    fmt::{self, Display},
+    time::Duration,
+};
 
 /// Holds a chat token obtained via the api to authenticate
 /// a chat session.
+///
+/// [`ChatMessageStream`](crate::chat::ChatMessageStream) uses the token to connect for you. To
+/// use your own websocket client instead, connect to [`CHAT_URL`](crate::chat::CHAT_URL) and
+/// send an auth message with the token first:
+///
+/// ```
+/// use trovo::chat::{ChatSocketMessage, ChatToken};
+///
+/// let token = ChatToken {
+///     token: "chat token".to_string(),
+/// };
+/// let auth = ChatSocketMessage::Auth {
+///     nonce: "auth".to_string(),
+///     data: token,
+/// };
+/// let text = serde_json::to_string(&auth).unwrap();
+/// ```
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ChatToken {
     /// Chat token to authenticate to chat with
@@ -89,53 +110,102 @@ pub struct ChatMessageData {
 }
 
 /// Type of the chat message
-#[derive(Serialize_repr, Deserialize_repr, PartialEq, Debug)]
-#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "u16", into = "u16")]
 pub enum ChatMessageType {
     /// Normal chat messages.
-    Normal = 0,
+    Normal,
 
     /// Spells, including: mana spells, elixir spells
-    Spell = 5,
+    Spell,
 
     /// Magic chat - super cap chat
-    MagicSuperCap = 6,
+    MagicSuperCap,
 
     /// Magic chat - colorful chat
-    MagicColorful = 7,
+    MagicColorful,
 
     /// Magic chat - spell chat
-    MagicSpell = 8,
+    MagicSpell,
 
     /// Magic chat - bullet screen chat
-    MagicBulletScreen = 9,
+    MagicBulletScreen,
 
     /// Subscription message. Shows when someone subscribes to the channel.
-    Subscription = 5001,
+    Subscription,
 
     /// System message.
-    System = 5002,
+    System,
 
     /// Follow message. Shows when someone follows the channel.
-    Follow = 5003,
+    Follow,
 
     /// Welcome message when viewer joins the channel.
-    Welcome = 5004,
+    Welcome,
 
     /// Gift sub message. When a user randomly sends gift subscriptions to one or more users in the channel.
-    GiftSub = 5005,
+    GiftSub,
 
     /// Gift sub message. The detailed messages when a user sends a gift subscription to another user.
-    GiftSubDetailed = 5006,
+    GiftSubDetailed,
 
     /// Activity / events message. For platform level events.
-    Event = 5007,
+    Event,
 
     /// Welcome message when users join the channel from raid.
-    Raid = 5008,
+    Raid,
 
     /// Custom Spells
-    CustomSpell = 5009,
+    CustomSpell,
+
+    /// A type this crate doesn't know about yet, with its number
+    Unknown(u16),
+}
+
+impl From<u16> for ChatMessageType {
+    fn from(type_: u16) -> Self {
+        match type_ {
+            0 => Self::Normal,
+            5 => Self::Spell,
+            6 => Self::MagicSuperCap,
+            7 => Self::MagicColorful,
+            8 => Self::MagicSpell,
+            9 => Self::MagicBulletScreen,
+            5001 => Self::Subscription,
+            5002 => Self::System,
+            5003 => Self::Follow,
+            5004 => Self::Welcome,
+            5005 => Self::GiftSub,
+            5006 => Self::GiftSubDetailed,
+            5007 => Self::Event,
+            5008 => Self::Raid,
+            5009 => Self::CustomSpell,
+            type_ => Self::Unknown(type_),
+        }
+    }
+}
+
+impl From<ChatMessageType> for u16 {
+    fn from(type_: ChatMessageType) -> Self {
+        match type_ {
+            ChatMessageType::Normal => 0,
+            ChatMessageType::Spell => 5,
+            ChatMessageType::MagicSuperCap => 6,
+            ChatMessageType::MagicColorful => 7,
+            ChatMessageType::MagicSpell => 8,
+            ChatMessageType::MagicBulletScreen => 9,
+            ChatMessageType::Subscription => 5001,
+            ChatMessageType::System => 5002,
+            ChatMessageType::Follow => 5003,
+            ChatMessageType::Welcome => 5004,
+            ChatMessageType::GiftSub => 5005,
+            ChatMessageType::GiftSubDetailed => 5006,
+            ChatMessageType::Event => 5007,
+            ChatMessageType::Raid => 5008,
+            ChatMessageType::CustomSpell => 5009,
+            ChatMessageType::Unknown(type_) => type_,
+        }
+    }
 }
 
 /// A single chat message
@@ -190,6 +260,558 @@ pub struct ChatMessage {
     pub custom_role: Option<String>,
 }
 
+impl ChatMessage {
+    /// What kind of message this is, with the details that are specific to it
+    pub fn kind(&self) -> ChatMessageKind {
+        let content = self.content.clone();
+        match self.type_ {
+            ChatMessageType::Normal => ChatMessageKind::Chat { content },
+            ChatMessageType::Spell => ChatMessageKind::Spell {
+                spell: self.spell(),
+                content,
+            },
+            ChatMessageType::MagicSuperCap => ChatMessageKind::MagicChat {
+                style: MagicChatStyle::SuperCap,
+                content,
+            },
+            ChatMessageType::MagicColorful => ChatMessageKind::MagicChat {
+                style: MagicChatStyle::Colorful,
+                content,
+            },
+            ChatMessageType::MagicSpell => ChatMessageKind::MagicChat {
+                style: MagicChatStyle::Spell,
+                content,
+            },
+            ChatMessageType::MagicBulletScreen => ChatMessageKind::MagicChat {
+                style: MagicChatStyle::BulletScreen,
+                content,
+            },
+            ChatMessageType::Subscription => ChatMessageKind::Subscription {
+                tier: self.sub_lv.clone(),
+                content,
+            },
+            ChatMessageType::System => ChatMessageKind::System { content },
+            ChatMessageType::Follow => ChatMessageKind::Follow,
+            ChatMessageType::Welcome => ChatMessageKind::Welcome(self.viewer_join()),
+            ChatMessageType::GiftSub | ChatMessageType::GiftSubDetailed => {
+                ChatMessageKind::GiftSub {
+                    gift: self.gift_sub(),
+                    content,
+                }
+            }
+            ChatMessageType::Event => ChatMessageKind::Event { content },
+            ChatMessageType::Raid => ChatMessageKind::Raid {
+                raid: self.incoming_raid(),
+                content,
+            },
+            ChatMessageType::CustomSpell => ChatMessageKind::CustomSpell {
+                spell: self.spell(),
+                content,
+            },
+            ChatMessageType::Unknown(message_type) => ChatMessageKind::Unknown {
+                message_type,
+                content,
+            },
+        }
+    }
+
+    /// The spell cast, for [`ChatMessageType::Spell`] and [`ChatMessageType::CustomSpell`]
+    /// messages whose content could be parsed
+    pub fn spell(&self) -> Option<SpellCast> {
+        match self.type_ {
+            ChatMessageType::Spell | ChatMessageType::CustomSpell => {
+                serde_json::from_str(&self.content).ok()
+            }
+            _ => None,
+        }
+    }
+
+    /// Split the message's content into text and the emotes in it, in order, for rendering the
+    /// emotes as images.
+    ///
+    /// Trovo sends emotes in the content as their codes, such as `:happy`, so emotes are found
+    /// by matching whitespace separated words against the given emotes, such as those from
+    /// [`Client::emotes`](crate::Client::emotes) with the channel's id.
+    pub fn fragments<'a>(&'a self, emotes: &'a EmoteChannels) -> Vec<ChatFragment<'a>> {
+        let urls = emotes.urls_by_code();
+        let content = self.content.as_str();
+        let mut fragments = Vec::new();
+        let mut text_start = 0;
+        let mut word_start = None;
+        // A trailing space ends the last word
+        let chars = content.char_indices().chain(Some((content.len(), ' ')));
+        for (i, c) in chars {
+            match (word_start, c.is_whitespace()) {
+                (None, false) => word_start = Some(i),
+                (Some(start), true) => {
+                    word_start = None;
+                    let word = &content[start..i];
+                    if let Some(url) = urls.get(word) {
+                        if text_start < start {
+                            fragments.push(ChatFragment::Text(&content[text_start..start]));
+                        }
+                        fragments.push(ChatFragment::Emote { code: word, url });
+                        text_start = i;
+                    }
+                }
+                _ => {}
+            }
+        }
+        if text_start < content.len() {
+            fragments.push(ChatFragment::Text(&content[text_start..]));
+        }
+        fragments
+    }
+
+    /// The sender's [`roles`](ChatMessage::roles), for checking their permissions
+    pub fn sender_roles(&self) -> Roles<'_> {
+        Roles(&self.roles)
+    }
+
+    /// The sender's badges, decoded from their [`medals`](ChatMessage::medals) and
+    /// [`decos`](ChatMessage::decos)
+    pub fn badges(&self) -> Vec<Badge> {
+        self.medals
+            .iter()
+            .chain(&self.decos)
+            .map(|name| Badge::from(name.as_str()))
+            .collect()
+    }
+
+    /// Whether the message was written by the sender, rather than sent by Trovo for something
+    /// they did such as joining, following or casting a spell
+    pub fn is_chat(&self) -> bool {
+        matches!(
+            self.type_,
+            ChatMessageType::Normal
+                | ChatMessageType::MagicSuperCap
+                | ChatMessageType::MagicColorful
+                | ChatMessageType::MagicSpell
+                | ChatMessageType::MagicBulletScreen
+        )
+    }
+
+    /// The viewer who joined, for [`ChatMessageType::Welcome`] messages
+    pub fn welcome(&self) -> Option<ViewerJoin> {
+        if self.type_ != ChatMessageType::Welcome {
+            return None;
+        }
+        Some(self.viewer_join())
+    }
+
+    /// The channel raiding this one, for [`ChatMessageType::Raid`] messages
+    pub fn raid(&self) -> Option<IncomingRaid> {
+        if self.type_ != ChatMessageType::Raid {
+            return None;
+        }
+        Some(self.incoming_raid())
+    }
+
+    fn incoming_raid(&self) -> IncomingRaid {
+        IncomingRaid {
+            raider: self.nick_name.clone(),
+            raider_id: self.sender_id,
+            viewers: self
+                .content
+                .split(|c: char| !c.is_ascii_digit())
+                .find_map(|part| part.parse().ok()),
+        }
+    }
+
+    fn viewer_join(&self) -> ViewerJoin {
+        ViewerJoin {
+            user_id: self.sender_id,
+            nick_name: self.nick_name.clone(),
+            roles: self.roles.clone(),
+            joined_at: self.send_time,
+        }
+    }
+
+    /// The subscriptions given, for [`ChatMessageType::GiftSub`] and
+    /// [`ChatMessageType::GiftSubDetailed`] messages
+    ///
+    /// The api only sends the number of subscriptions or the recipient as text in the content, so
+    /// they're None if it isn't in the expected format.
+    pub fn gift_sub(&self) -> Option<GiftSubscription> {
+        let (count, recipient) = match self.type_ {
+            ChatMessageType::GiftSub => (
+                self.content
+                    .rsplit(|c: char| c == ',' || c.is_whitespace())
+                    .find_map(|part| part.parse().ok()),
+                None,
+            ),
+            ChatMessageType::GiftSubDetailed => {
+                let recipient = self.content.rsplit(',').next().unwrap_or_default().trim();
+                (
+                    Some(1),
+                    Some(recipient.to_string()).filter(|r| !r.is_empty()),
+                )
+            }
+            _ => return None,
+        };
+        Some(GiftSubscription {
+            gifter: self.nick_name.clone(),
+            gifter_id: self.sender_id,
+            count,
+            recipient,
+            tier: self.sub_lv.clone(),
+        })
+    }
+}
+
+/// The kind of a [`ChatMessage`], from [`ChatMessage::kind`].
+///
+/// The sender is always the message's [`nick_name`](ChatMessage::nick_name) and
+/// [`sender_id`](ChatMessage::sender_id).
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum ChatMessageKind {
+    /// A normal chat message
+    Chat {
+        /// Text of the message
+        content: String,
+    },
+
+    /// A mana or elixir spell was cast
+    Spell {
+        /// The spell, or None if the content couldn't be parsed
+        spell: Option<SpellCast>,
+        /// The spell, as json
+        content: String,
+    },
+
+    /// A paid chat message that stands out in chat
+    MagicChat {
+        /// How the message is shown
+        style: MagicChatStyle,
+        /// Text of the message
+        content: String,
+    },
+
+    /// The sender subscribed to the channel
+    Subscription {
+        /// Subscription level, such as `sub_L1` for tier 1
+        tier: Option<String>,
+        /// Text shown in chat
+        content: String,
+    },
+
+    /// A message from Trovo
+    System {
+        /// Text of the message
+        content: String,
+    },
+
+    /// The sender followed the channel
+    Follow,
+
+    /// The sender joined the channel
+    Welcome(ViewerJoin),
+
+    /// The sender gave subscriptions to random viewers, or one of those subscriptions was given
+    /// to a viewer
+    GiftSub {
+        /// The subscriptions given
+        gift: Option<GiftSubscription>,
+        /// Text shown in chat
+        content: String,
+    },
+
+    /// A platform level event
+    Event {
+        /// Text shown in chat
+        content: String,
+    },
+
+    /// The sender raided the channel
+    Raid {
+        /// The channel raiding
+        raid: IncomingRaid,
+        /// Text shown in chat
+        content: String,
+    },
+
+    /// A spell set up by the channel was cast
+    CustomSpell {
+        /// The spell, or None if the content couldn't be parsed
+        spell: Option<SpellCast>,
+        /// The spell, as json
+        content: String,
+    },
+
+    /// A message of a type this crate doesn't know about yet
+    Unknown {
+        /// The type's number
+        message_type: u16,
+        /// Content of the message
+        content: String,
+    },
+}
+
+/// A spell cast in chat, from [`ChatMessage::spell`]
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpellCast {
+    /// Name of the spell
+    #[serde(rename = "gift")]
+    pub name: String,
+
+    /// How many of the spell were cast at once
+    #[serde(rename = "num", default = "one")]
+    pub quantity: u32,
+
+    /// Id of the spell
+    #[serde_as(as = "Option<PickFirst<(_, DisplayFromStr)>>")]
+    #[serde(default)]
+    pub gift_id: Option<u64>,
+
+    /// Price of one of the spell, in [`SpellCast::currency`]
+    #[serde_as(as = "Option<PickFirst<(_, DisplayFromStr)>>")]
+    #[serde(rename = "gift_value", default)]
+    pub value: Option<u64>,
+
+    /// Currency the spell was paid for with
+    #[serde(rename = "value_type", default)]
+    pub currency: Option<SpellCurrency>,
+}
+
+fn one() -> u32 {
+    1
+}
+
+impl SpellCast {
+    /// Price of every spell cast, in [`SpellCast::currency`], or None if the value is unknown or
+    /// too large
+    pub fn total_value(&self) -> Option<u64> {
+        self.value?.checked_mul(u64::from(self.quantity))
+    }
+}
+
+/// Currency a spell is paid for with
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum SpellCurrency {
+    /// Mana, earned by watching streams
+    Mana,
+
+    /// Elixir, bought with real money
+    Elixir,
+
+    /// A currency this crate doesn't know about
+    Other(String),
+}
+
+impl From<String> for SpellCurrency {
+    fn from(currency: String) -> Self {
+        match currency.as_str() {
+            "Mana" => Self::Mana,
+            "Elixir" => Self::Elixir,
+            _ => Self::Other(currency),
+        }
+    }
+}
+
+impl From<SpellCurrency> for String {
+    fn from(currency: SpellCurrency) -> Self {
+        match currency {
+            SpellCurrency::Mana => "Mana".to_string(),
+            SpellCurrency::Elixir => "Elixir".to_string(),
+            SpellCurrency::Other(currency) => currency,
+        }
+    }
+}
+
+/// Part of a chat message's content, from [`ChatMessage::fragments`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChatFragment<'a> {
+    /// Plain text, including any whitespace around emotes
+    Text(&'a str),
+
+    /// An emote
+    Emote {
+        /// The emote's code, such as `:happy`
+        code: &'a str,
+        /// Url of the emote's image
+        url: &'a str,
+    },
+}
+
+/// The roles of a chat sender in the channel, from [`ChatMessage::sender_roles`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Roles<'a>(&'a [String]);
+
+impl<'a> Roles<'a> {
+    /// Whether the sender has the role with the given name, including the channel's custom
+    /// roles
+    pub fn has(&self, role: &str) -> bool {
+        self.0.iter().any(|r| r == role)
+    }
+
+    /// Iterate over the names of the roles
+    pub fn iter(&self) -> impl Iterator<Item = &'a str> {
+        self.0.iter().map(String::as_str)
+    }
+
+    /// Whether the sender is the channel's streamer
+    pub fn is_streamer(&self) -> bool {
+        self.has("streamer")
+    }
+
+    /// Whether the sender is a moderator of the channel. This doesn't include the streamer, see
+    /// [`Roles::can_moderate`].
+    pub fn is_moderator(&self) -> bool {
+        self.has("mod")
+    }
+
+    /// Whether the sender is subscribed to the channel
+    pub fn is_subscriber(&self) -> bool {
+        self.has("subscriber")
+    }
+
+    /// Whether the sender follows the channel
+    pub fn is_follower(&self) -> bool {
+        self.has("follower")
+    }
+
+    /// Whether the sender can moderate the channel's chat, as its streamer, a moderator, or a
+    /// Trovo admin or warden
+    pub fn can_moderate(&self) -> bool {
+        self.is_streamer()
+            || self.is_moderator()
+            || ["supermod", "admin", "warden"]
+                .iter()
+                .any(|role| self.has(role))
+    }
+}
+
+/// A badge shown next to a chat sender's name, from [`ChatMessage::badges`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Badge {
+    /// The channel's streamer
+    Streamer,
+
+    /// A moderator of the channel
+    Moderator,
+
+    /// A Trovo admin
+    Admin,
+
+    /// An editor of the channel
+    Editor,
+
+    /// A warden, a platform level moderator
+    Warden,
+
+    /// A subscriber of the channel, such as `sub_L1_T2`
+    Subscriber {
+        /// Subscription level, from how long the viewer has been subscribed
+        level: Option<u32>,
+        /// Subscription tier
+        tier: Option<u32>,
+    },
+
+    /// A badge from a platform event, with its full name
+    Event(String),
+
+    /// A badge this crate doesn't know about, with its name
+    Unknown(String),
+}
+
+impl From<&str> for Badge {
+    fn from(name: &str) -> Self {
+        match name {
+            "creator" | "streamer" => Self::Streamer,
+            "moderator" | "mod" => Self::Moderator,
+            "admin" | "supermod" => Self::Admin,
+            "editor" => Self::Editor,
+            "warden" | "wardens" => Self::Warden,
+            _ if name.starts_with("sub_") => {
+                let number = |prefix: char| {
+                    name.split('_')
+                        .find_map(|part| part.strip_prefix(prefix)?.parse().ok())
+                };
+                Self::Subscriber {
+                    level: number('L'),
+                    tier: number('T'),
+                }
+            }
+            _ if name.starts_with("event_") => Self::Event(name.to_string()),
+            _ => Self::Unknown(name.to_string()),
+        }
+    }
+}
+
+/// A viewer joining the channel, from [`ChatMessage::welcome`].
+///
+/// Trovo sends a welcome message each time a viewer opens the channel, so the same viewer can
+/// join many times a stream, and joins are also sent again in the recent messages on connecting
+/// to chat. To greet viewers only once, remember the [`user_id`](ViewerJoin::user_id)s already
+/// greeted. Welcome messages aren't written by the viewer, see [`ChatMessage::is_chat`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ViewerJoin {
+    /// User id of the viewer
+    pub user_id: Option<i64>,
+
+    /// Display name of the viewer
+    pub nick_name: String,
+
+    /// Roles of the viewer in the channel, such as `follower` or `mod`
+    pub roles: Vec<String>,
+
+    /// When the viewer joined
+    pub joined_at: DateTime<Utc>,
+}
+
+/// Another channel raiding this one, from [`ChatMessage::raid`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IncomingRaid {
+    /// Display name of the raiding streamer
+    pub raider: String,
+
+    /// User id of the raiding streamer
+    pub raider_id: Option<i64>,
+
+    /// How many viewers joined from the raid, if the api included it in the message
+    pub viewers: Option<u32>,
+}
+
+/// Subscriptions given by a viewer, from [`ChatMessage::gift_sub`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GiftSubscription {
+    /// Display name of the viewer who gave the subscriptions
+    pub gifter: String,
+
+    /// User id of the viewer who gave the subscriptions
+    pub gifter_id: Option<i64>,
+
+    /// How many subscriptions were given
+    pub count: Option<u32>,
+
+    /// Display name of the viewer who received the subscription, for
+    /// [`ChatMessageType::GiftSubDetailed`] messages
+    pub recipient: Option<String>,
+
+    /// Subscription level, such as `sub_L1` for tier 1
+    pub tier: Option<String>,
+}
+
+/// How a [`ChatMessageKind::MagicChat`] message is shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MagicChatStyle {
+    /// Super cap chat
+    SuperCap,
+
+    /// Colorful chat
+    Colorful,
+
+    /// Spell chat
+    Spell,
+
+    /// Bullet screen chat, shown across the stream
+    BulletScreen,
+}
+
 /// Payload for the send chat message request
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SendChatMessagePayload {
@@ -200,3 +822,376 @@ pub struct SendChatMessagePayload {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub channel_id: Option<String>,
 }
+
+/// A chat command to perform in a channel with
+/// [`Client::chat_command`](crate::Client::chat_command)
+///
+/// Users are given by their username.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ChatCommand {
+    /// Ban a user from chatting, for the given duration or permanently
+    Ban {
+        /// Username of the user
+        user: String,
+        /// How long to ban the user for, rounded up to whole seconds. Bans permanently if None.
+        duration: Option<Duration>,
+    },
+
+    /// Lift a user's ban
+    Unban(String),
+
+    /// Make a user a moderator of the channel
+    Mod(String),
+
+    /// Remove a user's moderator role
+    Unmod(String),
+
+    /// Give a user a custom role of the channel
+    AddRole {
+        /// Name of the role
+        role: String,
+        /// Username of the user
+        user: String,
+    },
+
+    /// Remove a custom role from a user
+    RemoveRole {
+        /// Name of the role
+        role: String,
+        /// Username of the user
+        user: String,
+    },
+
+    /// Delete every message in chat
+    Clear,
+
+    /// Only allow each user to send one message per given interval, rounded up to whole seconds
+    Slow(Duration),
+
+    /// Turn slow mode off
+    SlowOff,
+
+    /// Only allow followers to chat
+    FollowersOnly,
+
+    /// Allow everyone to chat again
+    FollowersOnlyOff,
+
+    /// Host another channel, by its username
+    Host(String),
+
+    /// Stop hosting
+    Unhost,
+
+    /// Change the channel's title
+    SetTitle(String),
+
+    /// Change the channel's category, by name
+    SetCategory(String),
+
+    /// Any other command, without the leading `/`, such as `ban username 60`
+    Custom(String),
+}
+
+impl Display for ChatCommand {
+    /// Formats the command as it's sent to the api, without the leading `/`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ban {
+                user,
+                duration: Some(duration),
+            } => write!(f, "ban {} {}", user, whole_secs(*duration)),
+            Self::Ban {
+                user,
+                duration: None,
+            } => write!(f, "ban {}", user),
+            Self::Unban(user) => write!(f, "unban {}", user),
+            Self::Mod(user) => write!(f, "mod {}", user),
+            Self::Unmod(user) => write!(f, "unmod {}", user),
+            Self::AddRole { role, user } => write!(f, "addrole {} {}", role, user),
+            Self::RemoveRole { role, user } => write!(f, "removerole {} {}", role, user),
+            Self::Clear => f.write_str("clear"),
+            Self::Slow(interval) => write!(f, "slow {}", whole_secs(*interval)),
+            Self::SlowOff => f.write_str("slowoff"),
+            Self::FollowersOnly => f.write_str("followers"),
+            Self::FollowersOnlyOff => f.write_str("followersoff"),
+            Self::Host(channel) => write!(f, "host {}", channel),
+            Self::Unhost => f.write_str("unhost"),
+            Self::SetTitle(title) => write!(f, "settitle {}", title),
+            Self::SetCategory(category) => write!(f, "setcategory {}", category),
+            Self::Custom(command) => f.write_str(command.trim_start_matches('/')),
+        }
+    }
+}
+
+impl ChatCommand {
+    /// Why the command can't be sent, if a username or role is empty or has whitespace, which
+    /// would be read as more arguments to the command
+    pub(crate) fn invalid_argument(&self) -> Option<String> {
+        let words: &[&String] = match self {
+            Self::Ban { user, .. }
+            | Self::Unban(user)
+            | Self::Mod(user)
+            | Self::Unmod(user)
+            | Self::Host(user) => &[user],
+            Self::AddRole { role, user } | Self::RemoveRole { role, user } => &[role, user],
+            _ => &[],
+        };
+        words
+            .iter()
+            .find(|word| word.is_empty() || word.contains(char::is_whitespace))
+            .map(|word| format!("{:?} isn't a single word", word))
+    }
+}
+
+/// Seconds in the duration, rounded up so that short durations aren't sent as 0
+fn whole_secs(duration: Duration) -> u64 {
+    let secs = duration
+        .as_secs()
+        .saturating_add(u64::from(duration.subsec_nanos() > 0));
+    secs.max(1)
+}
+
+/// Payload for the chat command request
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ChatCommandPayload {
+    /// The command, without the leading `/`
+    pub command: String,
+
+    /// The channel to perform the command in
+    pub channel_id: String,
+}
+
+/// Result of a chat command
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ChatCommandResponse {
+    /// Whether the command succeeded
+    pub is_success: bool,
+
+    /// Message describing the result, as would be shown in chat
+    #[serde(default)]
+    pub display_msg: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(type_: u16, content: &str) -> ChatMessage {
+        serde_json::from_value(serde_json::json!({
+            "type": type_,
+            "content": content,
+            "nick_name": "someone",
+            "sub_lv": "sub_L1",
+            "message_id": "1",
+            "send_time": 1600000000,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn messages_have_kinds() {
+        assert_eq!(
+            message(0, "hi").kind(),
+            ChatMessageKind::Chat {
+                content: "hi".to_string()
+            }
+        );
+        assert_eq!(
+            message(7, "hi").kind(),
+            ChatMessageKind::MagicChat {
+                style: MagicChatStyle::Colorful,
+                content: "hi".to_string()
+            }
+        );
+        assert!(matches!(
+            message(5001, "").kind(),
+            ChatMessageKind::Subscription { tier: Some(tier), .. } if tier == "sub_L1"
+        ));
+        assert_eq!(message(5003, "").kind(), ChatMessageKind::Follow);
+        assert_eq!(
+            message(6001, "hi").kind(),
+            ChatMessageKind::Unknown {
+                message_type: 6001,
+                content: "hi".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn emotes_are_split_from_text() {
+        let emotes = EmoteChannels {
+            customized_emotes: crate::CustomizedEmotes {
+                channel: Vec::new(),
+            },
+            event_emotes: Vec::new(),
+            global_emotes: vec![serde_json::from_value(serde_json::json!({
+                "name": "happy",
+                "description": "",
+                "url": "happy.png",
+                "status": "",
+            }))
+            .unwrap()],
+        };
+        let msg = message(0, ":happy hi :happy :sad :happy");
+        assert_eq!(
+            msg.fragments(&emotes),
+            [
+                ChatFragment::Emote {
+                    code: ":happy",
+                    url: "happy.png"
+                },
+                ChatFragment::Text(" hi "),
+                ChatFragment::Emote {
+                    code: ":happy",
+                    url: "happy.png"
+                },
+                ChatFragment::Text(" :sad "),
+                ChatFragment::Emote {
+                    code: ":happy",
+                    url: "happy.png"
+                },
+            ]
+        );
+        assert_eq!(
+            message(0, "no emotes").fragments(&emotes),
+            [ChatFragment::Text("no emotes")]
+        );
+    }
+
+    #[test]
+    fn badges_are_decoded() {
+        let mut msg = message(0, "hi");
+        msg.medals = vec!["creator".to_string(), "sub_L2_T3".to_string()];
+        msg.decos = vec!["shiny".to_string()];
+        assert_eq!(
+            msg.badges(),
+            [
+                Badge::Streamer,
+                Badge::Subscriber {
+                    level: Some(2),
+                    tier: Some(3)
+                },
+                Badge::Unknown("shiny".to_string()),
+            ]
+        );
+        assert_eq!(
+            Badge::from("sub_L1"),
+            Badge::Subscriber {
+                level: Some(1),
+                tier: None
+            }
+        );
+    }
+
+    #[test]
+    fn roles_are_checked() {
+        let mut msg = message(0, "hi");
+        msg.roles = vec!["mod".to_string(), "follower".to_string()];
+        let roles = msg.sender_roles();
+        assert!(roles.is_moderator());
+        assert!(roles.is_follower());
+        assert!(roles.can_moderate());
+        assert!(!roles.is_streamer());
+        assert!(!roles.is_subscriber());
+
+        msg.roles = vec!["streamer".to_string()];
+        assert!(msg.sender_roles().can_moderate());
+        assert!(!msg.sender_roles().is_moderator());
+    }
+
+    #[test]
+    fn welcomes_are_not_chat() {
+        let msg = message(5004, "");
+        assert!(!msg.is_chat());
+        let welcome = msg.welcome().unwrap();
+        assert_eq!(welcome.nick_name, "someone");
+        assert!(matches!(msg.kind(), ChatMessageKind::Welcome(_)));
+
+        assert!(message(7, "hi").is_chat());
+        assert!(message(7, "hi").welcome().is_none());
+    }
+
+    #[test]
+    fn spells_are_parsed() {
+        let msg = message(
+            5,
+            r#"{"gift":"Wheat Bread","num":3,"gift_id":"101","gift_value":10,"value_type":"Elixir"}"#,
+        );
+        let spell = msg.spell().unwrap();
+        assert_eq!(spell.name, "Wheat Bread");
+        assert_eq!(spell.currency, Some(SpellCurrency::Elixir));
+        assert_eq!(spell.gift_id, Some(101));
+        assert_eq!(spell.total_value(), Some(30));
+
+        let spell = SpellCast {
+            value: Some(u64::MAX),
+            ..spell
+        };
+        assert_eq!(spell.total_value(), None);
+
+        assert!(message(5009, "not json").spell().is_none());
+        assert!(message(0, r#"{"gift":"Wheat Bread"}"#).spell().is_none());
+    }
+
+    #[test]
+    fn raids_are_parsed() {
+        let raid = message(5008, "someone is raiding with 42 viewers!")
+            .raid()
+            .unwrap();
+        assert_eq!(raid.raider, "someone");
+        assert_eq!(raid.viewers, Some(42));
+        assert_eq!(
+            message(5008, "welcome raiders").raid().unwrap().viewers,
+            None
+        );
+        assert!(message(0, "42").raid().is_none());
+    }
+
+    #[test]
+    fn gift_subs_are_parsed() {
+        let gift = message(5005, "someone,5").gift_sub().unwrap();
+        assert_eq!(gift.gifter, "someone");
+        assert_eq!(gift.count, Some(5));
+        assert_eq!(gift.recipient, None);
+        assert_eq!(gift.tier.as_deref(), Some("sub_L1"));
+
+        let gift = message(5006, "someone,lucky viewer").gift_sub().unwrap();
+        assert_eq!(gift.count, Some(1));
+        assert_eq!(gift.recipient.as_deref(), Some("lucky viewer"));
+    }
+
+    #[test]
+    fn commands_are_formatted() {
+        let ban = ChatCommand::Ban {
+            user: "spammer".to_string(),
+            duration: Some(Duration::from_secs(600)),
+        };
+        assert_eq!(ban.to_string(), "ban spammer 600");
+        assert_eq!(
+            ChatCommand::Slow(Duration::from_secs(5)).to_string(),
+            "slow 5"
+        );
+        assert_eq!(
+            ChatCommand::Slow(Duration::from_millis(500)).to_string(),
+            "slow 1"
+        );
+        assert_eq!(
+            ChatCommand::Slow(Duration::MAX).to_string(),
+            format!("slow {}", u64::MAX)
+        );
+        assert_eq!(ChatCommand::FollowersOnlyOff.to_string(), "followersoff");
+        assert_eq!(ban.invalid_argument(), None);
+
+        let injected = ChatCommand::AddRole {
+            role: "Regulars".to_string(),
+            user: "someone mod".to_string(),
+        };
+        assert!(injected.invalid_argument().is_some());
+        assert_eq!(
+            ChatCommand::Custom("/fastclip".to_string()).to_string(),
+            "fastclip"
+        );
+    }
+}
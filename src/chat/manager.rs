@@ -0,0 +1,255 @@
+use crate::{
+    chat::{ChatEvent, ChatMessagesForChannelError, ReconnectingChatStream},
+    Client, ClientIdProvider,
+};
+use futures::{Stream, StreamExt};
+use std::{
+    fmt::{self, Debug},
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tokio::sync::mpsc;
+
+type Connect = Box<dyn Fn(String) -> ReconnectingChatStream<ChatMessagesForChannelError> + Send>;
+
+/// Connections to the chats of many channels, as one stream of chat events tagged with the id
+/// of the channel they happened in.
+///
+/// Channels can be joined and left at any time, including from other tasks through a
+/// [`ChatManagerHandle`]. Each channel has its own connection, which reconnects like
+/// [`Client::chat_events_for_channel`] and keeps itself alive with its own pings. If a
+/// connection gives up reconnecting, its channel is left with a [`ChatEvent::Disconnected`]
+/// event after the error, and can be joined again.
+///
+/// ```no_run
+/// # async fn run(client: trovo::Client<trovo::ClientId>) {
+/// use futures::StreamExt;
+/// use trovo::chat::{ChatEvent, ChatManager};
+///
+/// let mut chats = ChatManager::new(client);
+/// chats.join("first channel id");
+/// chats.join("second channel id");
+/// while let Some((channel_id, event)) = chats.next().await {
+///     match event {
+///         Ok(ChatEvent::Message(msg)) => println!("{}: [{}] {}", channel_id, msg.nick_name, msg.content),
+///         Ok(_) => {}
+///         Err(err) => eprintln!("{}: {}", channel_id, err),
+///     }
+/// }
+/// # }
+/// ```
+#[must_use = "streams do nothing unless polled"]
+pub struct ChatManager {
+    connect: Connect,
+    channels: Vec<(String, ReconnectingChatStream<ChatMessagesForChannelError>)>,
+    handle: ChatManagerHandle,
+    commands: mpsc::UnboundedReceiver<Command>,
+    /// Channel to poll first, so that busy channels don't starve the others
+    next: usize,
+}
+
+#[derive(Debug)]
+enum Command {
+    Join(String),
+    Part(String),
+}
+
+/// Joins and leaves channels of a [`ChatManager`] from other tasks, from
+/// [`ChatManager::handle`].
+#[derive(Debug, Clone)]
+pub struct ChatManagerHandle {
+    commands: mpsc::UnboundedSender<Command>,
+}
+
+impl ChatManager {
+    /// Create a manager that connects to chats using the client, without joining any channels
+    pub fn new<A>(client: Client<A>) -> Self
+    where
+        A: ClientIdProvider + Clone + Send + Sync + 'static,
+    {
+        Self::from_connect(Box::new(move |channel_id| {
+            client.chat_events_for_channel(channel_id)
+        }))
+    }
+
+    fn from_connect(connect: Connect) -> Self {
+        let (sender, commands) = mpsc::unbounded_channel();
+        Self {
+            connect,
+            channels: Vec::new(),
+            handle: ChatManagerHandle { commands: sender },
+            commands,
+            next: 0,
+        }
+    }
+
+    /// Connect to the chat of the given channel id. Does nothing if it's already joined.
+    ///
+    /// Channels are joined the next time the manager is polled.
+    pub fn join(&self, channel_id: impl Into<String>) {
+        self.handle.join(channel_id)
+    }
+
+    /// Disconnect from the chat of the given channel id. Does nothing if it isn't joined.
+    pub fn part(&self, channel_id: impl Into<String>) {
+        self.handle.part(channel_id)
+    }
+
+    /// A handle to join and leave channels from other tasks
+    pub fn handle(&self) -> ChatManagerHandle {
+        self.handle.clone()
+    }
+
+    fn run(&mut self, command: Command) {
+        match command {
+            Command::Join(channel_id) => {
+                if !self.channels.iter().any(|(id, _)| *id == channel_id) {
+                    debug!(%channel_id, "joining chat");
+                    let events = (self.connect)(channel_id.clone());
+                    self.channels.push((channel_id, events));
+                }
+            }
+            Command::Part(channel_id) => {
+                debug!(%channel_id, "leaving chat");
+                self.channels.retain(|(id, _)| *id != channel_id);
+            }
+        }
+    }
+}
+
+impl ChatManagerHandle {
+    /// See [`ChatManager::join`]
+    pub fn join(&self, channel_id: impl Into<String>) {
+        self.commands.send(Command::Join(channel_id.into())).ok();
+    }
+
+    /// See [`ChatManager::part`]
+    pub fn part(&self, channel_id: impl Into<String>) {
+        self.commands.send(Command::Part(channel_id.into())).ok();
+    }
+}
+
+impl Stream for ChatManager {
+    type Item = (String, Result<ChatEvent, ChatMessagesForChannelError>);
+
+    /// The next event from any channel. Waits while no channels are joined rather than ending.
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        while let Poll::Ready(Some(command)) = self.commands.poll_recv(cx) {
+            self.run(command);
+        }
+
+        let len = self.channels.len();
+        for i in 0..len {
+            let index = (self.next + i) % len;
+            let (channel_id, events) = &mut self.channels[index];
+            match events.poll_next_unpin(cx) {
+                Poll::Ready(Some(event)) => {
+                    let item = (channel_id.clone(), event);
+                    self.next = index + 1;
+                    return Poll::Ready(Some(item));
+                }
+                Poll::Ready(None) => {
+                    let (channel_id, _) = self.channels.remove(index);
+                    debug!(%channel_id, "chat connection gave up, leaving chat");
+                    self.next = index;
+                    return Poll::Ready(Some((channel_id, Ok(ChatEvent::Disconnected))));
+                }
+                Poll::Pending => {}
+            }
+        }
+        Poll::Pending
+    }
+}
+
+impl Debug for ChatManager {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ChatManager")
+            .field(
+                "channels",
+                &self.channels.iter().map(|(id, _)| id).collect::<Vec<_>>(),
+            )
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        chat::{ChatConnectError, ChatMessage, ChatMessageStream},
+        RetryPolicy,
+    };
+
+    fn events(channel_id: String) -> ReconnectingChatStream<ChatMessagesForChannelError> {
+        ReconnectingChatStream::from_connect(
+            Box::new(move |_| {
+                let channel_id = channel_id.clone();
+                Box::pin(async move {
+                    let (sender, messages) = mpsc::channel(1);
+                    let msg: ChatMessage = serde_json::from_value(serde_json::json!({
+                        "type": 0,
+                        "content": channel_id,
+                        "nick_name": "someone",
+                        "message_id": "1",
+                        "send_time": 1600000000,
+                    }))
+                    .unwrap();
+                    sender.send(Ok(msg)).await.unwrap();
+                    // keep the connection open
+                    std::mem::forget(sender);
+                    Ok(ChatMessageStream::from_receiver(messages))
+                })
+            }),
+            RetryPolicy::new(1),
+            false,
+        )
+    }
+
+    #[tokio::test]
+    async fn events_are_tagged_with_channels() {
+        let mut chats = ChatManager::from_connect(Box::new(events));
+        chats.join("1");
+        chats.handle().join("2");
+        chats.join("1");
+
+        let mut seen = Vec::new();
+        for _ in 0..2 {
+            let (channel_id, event) = chats.next().await.unwrap();
+            match event.unwrap() {
+                ChatEvent::Message(msg) => assert_eq!(msg.content, channel_id),
+                event => panic!("unexpected event {:?}", event),
+            }
+            seen.push(channel_id);
+        }
+        seen.sort();
+        assert_eq!(seen, ["1", "2"]);
+
+        chats.part("1");
+        assert!(futures::poll!(chats.next()).is_pending());
+        assert_eq!(chats.channels.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn channels_that_give_up_are_left() {
+        let mut chats = ChatManager::from_connect(Box::new(|_| {
+            ReconnectingChatStream::from_connect(
+                Box::new(|_| Box::pin(async { Err(ChatConnectError::SocketClosed.into()) })),
+                RetryPolicy::new(1),
+                false,
+            )
+        }));
+        chats.join("1");
+
+        let (channel_id, event) = chats.next().await.unwrap();
+        assert_eq!(channel_id, "1");
+        assert!(event.is_err());
+        assert!(matches!(
+            chats.next().await,
+            Some((channel_id, Ok(ChatEvent::Disconnected))) if channel_id == "1"
+        ));
+        assert!(chats.channels.is_empty());
+
+        chats.join("1");
+        assert!(matches!(chats.next().await, Some((_, Err(_)))));
+    }
+}
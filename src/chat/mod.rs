@@ -2,11 +2,23 @@
 //!
 //! Connect to Trovo chat via websockets
 
+mod channel;
 mod client;
+mod config;
 mod entities;
 mod error;
+mod manager;
+mod proxy;
+mod reconnect;
 mod socket;
 
+pub(crate) use reconnect::default_reconnect_policy;
+
+pub use channel::*;
+pub use client::*;
+pub use config::*;
 pub use entities::*;
 pub use error::*;
+pub use manager::*;
+pub use reconnect::*;
 pub use socket::*;
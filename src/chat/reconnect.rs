@@ -0,0 +1,450 @@
+use crate::{
+    chat::{ChatConfig, ChatConnectError, ChatMessage, ChatMessageStream, ChatToken},
+    RetryPolicy,
+};
+use futures::{
+    future::BoxFuture,
+    stream::{self, BoxStream},
+    Future, Stream, StreamExt,
+};
+use std::{
+    fmt::{self, Debug},
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::time::{sleep, Instant};
+
+/// How long a connection has to stay up, if it doesn't return any messages, before the backoff
+/// starts over
+const HEALTHY_AFTER: Duration = Duration::from_secs(30);
+
+/// Something that happened on a [`ReconnectingChatStream`]
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ChatEvent {
+    /// A chat message was sent
+    Message(Box<ChatMessage>),
+
+    /// The connection dropped and a new one was made. Messages sent while disconnected are
+    /// returned afterwards if they're among the recent messages Trovo sends on connecting.
+    Reconnected,
+
+    /// The connection gave up reconnecting after its last error, so no more events follow.
+    ///
+    /// Only sent by [`ChatManager`](crate::chat::ChatManager), which leaves the channel so it
+    /// can be joined again. A [`ReconnectingChatStream`] just ends.
+    Disconnected,
+}
+
+/// Connects to chat, given whether to skip the history Trovo sends on connecting
+pub(crate) type Connect<E> =
+    Box<dyn FnMut(bool) -> BoxFuture<'static, Result<ChatMessageStream, E>> + Send>;
+
+/// A stream of chat events that reconnects, with a new chat token, whenever the connection
+/// drops.
+///
+/// Reconnecting waits a random backoff before each attempt as set by the [`RetryPolicy`]. The
+/// backoff keeps growing while connections fail or drop again straight away, and starts over
+/// once a connection returns a message or stays up for a while. The stream only ends, with the
+/// last error, once that many attempts in a row fail. See
+/// [`Client::chat_events_for_channel`](crate::Client::chat_events_for_channel) and
+/// [`Client::chat_events_for_user`](crate::Client::chat_events_for_user).
+#[must_use = "streams do nothing unless polled"]
+pub struct ReconnectingChatStream<E> {
+    inner: BoxStream<'static, Result<ChatEvent, E>>,
+}
+
+struct State<E> {
+    connect: Connect<E>,
+    retry: RetryPolicy,
+    messages: Option<ChatMessageStream>,
+    connected: bool,
+    done: bool,
+
+    /// Attempts in a row that failed to connect or dropped before the connection was healthy
+    attempt: u32,
+    connected_at: Instant,
+    /// Whether the current connection returned a message
+    delivered: bool,
+
+    /// Whether to skip the history on connecting, until a connection has skipped it
+    skip_history: bool,
+    /// Whether messages that were already returned are dropped, from reconnecting until the
+    /// first new message
+    deduplicating: bool,
+    /// Send time, in unix seconds, of the newest message returned or skipped and the ids of
+    /// the messages with that time, to drop them from the history sent after reconnecting
+    last_seen: Option<(i64, Vec<String>)>,
+}
+
+impl<E> ReconnectingChatStream<E>
+where
+    E: From<ChatConnectError> + Debug + Send + 'static,
+{
+    /// Connect with a chat token from `fetch_token`, and again with a new token each time the
+    /// connection drops.
+    ///
+    /// [`ChatConfig::skip_history`] only applies until a connection has received and skipped
+    /// the history. After that, reconnecting returns the recent messages that weren't already
+    /// returned or skipped.
+    pub fn new<F, Fut>(mut fetch_token: F, retry: RetryPolicy, config: ChatConfig) -> Self
+    where
+        F: FnMut() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<ChatToken, E>> + Send + 'static,
+    {
+        let skip_history = config.skip_history;
+        Self::from_connect(
+            Box::new(move |skip_history| {
+                let token = fetch_token();
+                let config = config.clone().skip_history(skip_history);
+                Box::pin(async move {
+                    let token = token.await?;
+                    Ok(ChatMessageStream::connect_with_config(token, &config).await?)
+                })
+            }),
+            retry,
+            skip_history,
+        )
+    }
+
+    pub(crate) fn from_connect(
+        connect: Connect<E>,
+        retry: RetryPolicy,
+        skip_history: bool,
+    ) -> Self {
+        let state = State {
+            connect,
+            retry,
+            messages: None,
+            connected: false,
+            done: false,
+            attempt: 0,
+            connected_at: Instant::now(),
+            delivered: false,
+            skip_history,
+            deduplicating: false,
+            last_seen: None,
+        };
+        Self {
+            inner: stream::unfold(state, State::next).boxed(),
+        }
+    }
+}
+
+impl<E: Debug> State<E> {
+    async fn next(mut self) -> Option<(Result<ChatEvent, E>, Self)> {
+        loop {
+            if self.done {
+                return None;
+            }
+            if let Some(messages) = &mut self.messages {
+                match messages.next().await {
+                    Some(Ok(msg)) if self.already_seen(&msg) => continue,
+                    Some(Ok(msg)) => {
+                        self.delivered = true;
+                        return Some((Ok(ChatEvent::Message(Box::new(msg))), self));
+                    }
+                    Some(Err(err)) => warn!(?err, "chat connection dropped, reconnecting"),
+                    None => warn!("chat connection closed, reconnecting"),
+                }
+                self.disconnected();
+                if self.delivered || self.connected_at.elapsed() >= HEALTHY_AFTER {
+                    self.attempt = 0;
+                } else {
+                    self.attempt = self.attempt.saturating_add(1);
+                }
+            }
+
+            let messages = loop {
+                if self.connected || self.attempt > 0 {
+                    let backoff = self.retry.backoff(self.attempt);
+                    debug!(
+                        ?backoff,
+                        attempt = self.attempt,
+                        "waiting to reconnect to chat"
+                    );
+                    sleep(backoff).await;
+                }
+                match (self.connect)(self.skip_history).await {
+                    Ok(messages) => break messages,
+                    Err(err) if self.attempt.saturating_add(1) < self.retry.max_attempts() => {
+                        warn!(?err, "failed to connect to chat, retrying");
+                        self.attempt += 1;
+                    }
+                    Err(err) => {
+                        self.done = true;
+                        return Some((Err(err), self));
+                    }
+                }
+            };
+            self.messages = Some(messages);
+            self.deduplicating = self.connected && !self.skip_history;
+            self.connected_at = Instant::now();
+            self.delivered = false;
+            if self.connected {
+                return Some((Ok(ChatEvent::Reconnected), self));
+            }
+            self.connected = true;
+        }
+    }
+}
+
+impl<E> State<E> {
+    /// Drop the connection, remembering the history it skipped
+    fn disconnected(&mut self) {
+        let messages = match self.messages.take() {
+            Some(messages) => messages,
+            None => return,
+        };
+        let history = messages.history.lock().unwrap();
+        if self.skip_history && history.skipped {
+            // Don't return the skipped history after reconnecting either
+            self.skip_history = false;
+            if self.last_seen.is_none() {
+                self.last_seen = history.newest.clone();
+            }
+        }
+    }
+
+    /// Whether the message is from the history sent after reconnecting and was already returned
+    /// or skipped, and otherwise remember it
+    fn already_seen(&mut self, msg: &ChatMessage) -> bool {
+        let time = msg.send_time.timestamp();
+        let seen = match &self.last_seen {
+            Some((last, ids)) => time < *last || (time == *last && ids.contains(&msg.message_id)),
+            None => false,
+        };
+        if self.deduplicating && seen {
+            return true;
+        }
+        // Messages after the first new one are returned even if they arrive out of order
+        self.deduplicating = false;
+        match &mut self.last_seen {
+            Some((last, _)) if time < *last => {}
+            Some((last, ids)) if time == *last => {
+                if !ids.contains(&msg.message_id) {
+                    ids.push(msg.message_id.clone());
+                }
+            }
+            _ => self.last_seen = Some((time, vec![msg.message_id.clone()])),
+        }
+        false
+    }
+}
+
+/// Reconnect policy for chat, retrying forever with a backoff of up to a minute
+pub(crate) fn default_reconnect_policy() -> RetryPolicy {
+    RetryPolicy::new(u32::MAX)
+        .with_initial_backoff(Duration::from_secs(1))
+        .with_max_backoff(Duration::from_secs(60))
+}
+
+impl<E> Stream for ReconnectingChatStream<E> {
+    type Item = Result<ChatEvent, E>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.poll_next_unpin(cx)
+    }
+}
+
+impl<E> Debug for ReconnectingChatStream<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReconnectingChatStream")
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chat::ChatMessageStreamError;
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+    use tokio::sync::mpsc;
+
+    fn message(id: &str) -> ChatMessage {
+        message_at(id, 1600000000)
+    }
+
+    fn message_at(id: &str, send_time: i64) -> ChatMessage {
+        serde_json::from_value(serde_json::json!({
+            "type": 0,
+            "content": "hi",
+            "nick_name": "someone",
+            "message_id": id,
+            "send_time": send_time,
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn reconnects_after_errors() {
+        let connects = Arc::new(AtomicUsize::new(0));
+        let counter = connects.clone();
+        let connect: Connect<ChatConnectError> = Box::new(move |_| {
+            let attempt = counter.fetch_add(1, Ordering::SeqCst);
+            Box::pin(async move {
+                if attempt == 1 {
+                    return Err(ChatConnectError::SocketClosed);
+                }
+                let (sender, messages) = mpsc::channel(3);
+                if attempt > 0 {
+                    // history from before reconnecting
+                    sender.send(Ok(message("0"))).await.unwrap();
+                }
+                sender
+                    .send(Ok(message(&attempt.to_string())))
+                    .await
+                    .unwrap();
+                sender
+                    .send(Err(ChatMessageStreamError::PingTimeout))
+                    .await
+                    .unwrap();
+                Ok(ChatMessageStream::from_receiver(messages))
+            })
+        });
+        let retry = RetryPolicy::new(2).with_initial_backoff(Duration::from_millis(1));
+        let mut events = ReconnectingChatStream::from_connect(connect, retry, false);
+
+        assert!(matches!(
+            events.next().await,
+            Some(Ok(ChatEvent::Message(msg))) if msg.message_id == "0"
+        ));
+        assert!(matches!(
+            events.next().await,
+            Some(Ok(ChatEvent::Reconnected))
+        ));
+        assert!(matches!(
+            events.next().await,
+            Some(Ok(ChatEvent::Message(msg))) if msg.message_id == "2"
+        ));
+        assert_eq!(connects.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn connections_that_drop_straight_away_count_as_failures() {
+        let connects = Arc::new(AtomicUsize::new(0));
+        let counter = connects.clone();
+        let connect: Connect<ChatConnectError> = Box::new(move |_| {
+            let attempt = counter.fetch_add(1, Ordering::SeqCst);
+            Box::pin(async move {
+                if attempt > 0 {
+                    return Err(ChatConnectError::SocketClosed);
+                }
+                let (sender, messages) = mpsc::channel(1);
+                sender
+                    .send(Err(ChatMessageStreamError::PingTimeout))
+                    .await
+                    .unwrap();
+                Ok(ChatMessageStream::from_receiver(messages))
+            })
+        });
+        let retry = RetryPolicy::new(2).with_initial_backoff(Duration::from_millis(1));
+        let mut events = ReconnectingChatStream::from_connect(connect, retry, false);
+
+        // The dropped connection was the first failure, so the failed reconnect is the last
+        assert!(matches!(
+            events.next().await,
+            Some(Err(ChatConnectError::SocketClosed))
+        ));
+        assert!(events.next().await.is_none());
+        assert_eq!(connects.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn skipped_history_stays_skipped_after_reconnecting() {
+        let skips = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorder = skips.clone();
+        let connect: Connect<ChatConnectError> = Box::new(move |skip_history| {
+            let mut skips = recorder.lock().unwrap();
+            skips.push(skip_history);
+            let attempt = skips.len();
+            Box::pin(async move {
+                let (sender, messages) = mpsc::channel(2);
+                let messages = ChatMessageStream::from_receiver(messages);
+                match attempt {
+                    // drops before the history arrives
+                    1 => {}
+                    // skips the history, then drops
+                    2 => {
+                        let mut history = messages.history.lock().unwrap();
+                        history.skipped = true;
+                        history.newest = Some((1600000000, vec!["old".to_string()]));
+                    }
+                    _ => {
+                        sender
+                            .send(Ok(message_at("older", 1599999999)))
+                            .await
+                            .unwrap();
+                        sender.send(Ok(message("old"))).await.unwrap();
+                        std::mem::forget(sender);
+                        return Ok(messages);
+                    }
+                }
+                sender
+                    .send(Err(ChatMessageStreamError::PingTimeout))
+                    .await
+                    .unwrap();
+                Ok(messages)
+            })
+        });
+        let retry = RetryPolicy::new(3).with_initial_backoff(Duration::from_millis(1));
+        let mut events = ReconnectingChatStream::from_connect(connect, retry, true);
+
+        for _ in 0..2 {
+            assert!(matches!(
+                events.next().await,
+                Some(Ok(ChatEvent::Reconnected))
+            ));
+        }
+        tokio::time::timeout(Duration::from_millis(50), events.next())
+            .await
+            .unwrap_err();
+        assert_eq!(*skips.lock().unwrap(), [true, true, false]);
+    }
+
+    #[tokio::test]
+    async fn only_history_is_deduplicated() {
+        let connects = Arc::new(AtomicUsize::new(0));
+        let counter = connects.clone();
+        let connect: Connect<ChatConnectError> = Box::new(move |_| {
+            let attempt = counter.fetch_add(1, Ordering::SeqCst);
+            Box::pin(async move {
+                let (sender, messages) = mpsc::channel(4);
+                if attempt == 0 {
+                    sender.send(Ok(message("1"))).await.unwrap();
+                    // arrives out of order
+                    sender.send(Ok(message_at("0", 1599999999))).await.unwrap();
+                    sender
+                        .send(Err(ChatMessageStreamError::PingTimeout))
+                        .await
+                        .unwrap();
+                } else {
+                    sender.send(Ok(message("1"))).await.unwrap();
+                    sender.send(Ok(message("2"))).await.unwrap();
+                    sender
+                        .send(Ok(message_at("late", 1599999999)))
+                        .await
+                        .unwrap();
+                    std::mem::forget(sender);
+                }
+                Ok(ChatMessageStream::from_receiver(messages))
+            })
+        });
+        let retry = RetryPolicy::new(2).with_initial_backoff(Duration::from_millis(1));
+        let mut events = ReconnectingChatStream::from_connect(connect, retry, false);
+
+        let mut ids = Vec::new();
+        for _ in 0..5 {
+            ids.push(match events.next().await {
+                Some(Ok(ChatEvent::Message(msg))) => msg.message_id,
+                Some(Ok(ChatEvent::Reconnected)) => "reconnected".to_string(),
+                event => panic!("unexpected event {:?}", event),
+            });
+        }
+        assert_eq!(ids, ["1", "0", "reconnected", "2", "late"]);
+    }
+}
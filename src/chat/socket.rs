@@ -1,27 +1,49 @@
 use crate::chat::{
-    ChatConnectError, ChatMessage, ChatMessageStreamError, ChatSocketMessage, ChatToken,
+    proxy, ChatConfig, ChatConnectError, ChatMessage, ChatMessageStreamError, ChatSocketMessage,
+    ChatToken,
 };
 use async_tungstenite::{
-    tokio::connect_async,
+    tokio::client_async_tls_with_connector_and_config,
     tungstenite::{self, Message},
 };
 use futures::prelude::*;
-use std::time::Duration;
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 use tokio::{
     select,
     sync::{mpsc, oneshot},
-    time::sleep,
+    time::{sleep_until, Instant},
 };
 use tokio_util::sync::CancellationToken;
+use url::Url;
 
 const CHAT_MESSAGES_BUFFER: usize = 32;
 const DEFAULT_PING_INTERVAL: Duration = Duration::from_secs(30);
+const CHAT_HOST: &str = "open-chat.trovo.live";
+const CHAT_PORT: u16 = 443;
+/// Url of Trovo's chat websocket, for connecting with your own websocket client
+pub const CHAT_URL: &str = "wss://open-chat.trovo.live/chat";
 
 /// A stream of chat messages
 #[derive(Debug)]
 pub struct ChatMessageStream {
     cancellation_token: CancellationToken,
     messages: mpsc::Receiver<Result<ChatMessage, ChatMessageStreamError>>,
+
+    /// The history skipped by the socket reader
+    pub(crate) history: Arc<Mutex<SkippedHistory>>,
+}
+
+/// The recent messages Trovo sends on connecting, once the socket reader has skipped them
+#[derive(Debug, Default)]
+pub(crate) struct SkippedHistory {
+    /// Whether the history was received and skipped
+    pub(crate) skipped: bool,
+
+    /// Send time, in unix seconds, of the newest skipped messages and their ids
+    pub(crate) newest: Option<(i64, Vec<String>)>,
 }
 
 impl ChatMessageStream {
@@ -31,8 +53,32 @@ impl ChatMessageStream {
     /// [`Client::chat_messages_for_user`](crate::Client::chat_messages_for_user) for fetching
     /// the token and connecting in one.
     pub async fn connect(chat_token: ChatToken) -> Result<ChatMessageStream, ChatConnectError> {
+        Self::connect_with_config(chat_token, &ChatConfig::default()).await
+    }
+
+    /// Connect to trovo chat using the given chat token, tunnelling the connection through an
+    /// `http://` proxy with the `CONNECT` method.
+    ///
+    /// Credentials in the proxy url are sent using basic auth.
+    pub async fn connect_via_proxy(
+        chat_token: ChatToken,
+        proxy: &Url,
+    ) -> Result<ChatMessageStream, ChatConnectError> {
+        Self::connect_with_config(chat_token, &ChatConfig::new().proxy(proxy.clone())).await
+    }
+
+    /// Connect to trovo chat using the given chat token and options.
+    pub async fn connect_with_config(
+        chat_token: ChatToken,
+        config: &ChatConfig,
+    ) -> Result<ChatMessageStream, ChatConnectError> {
         let cancellation_token = CancellationToken::new();
-        let (ws_stream, _) = connect_async("wss://open-chat.trovo.live/chat").await?;
+        let history = Arc::new(Mutex::new(SkippedHistory::default()));
+        let stream = proxy::connect(CHAT_HOST, CHAT_PORT, config.proxy.as_ref())
+            .await
+            .map_err(tungstenite::Error::Io)?;
+        let (ws_stream, _) =
+            client_async_tls_with_connector_and_config(CHAT_URL, stream, None, None).await?;
         let (mut writer, reader) = ws_stream.split();
         let (socket_messages_sender, socket_messages_receiver) = mpsc::channel(1);
         let (chat_messages_sender, chat_messages_receiver) = mpsc::channel(CHAT_MESSAGES_BUFFER);
@@ -47,6 +93,8 @@ impl ChatMessageStream {
             chat_messages_sender: chat_messages_sender.clone(),
             socket_messages_sender,
             ping: Default::default(),
+            skip_history: config.skip_history,
+            history: history.clone(),
         };
         reader.spawn();
 
@@ -71,9 +119,22 @@ impl ChatMessageStream {
         Ok(ChatMessageStream {
             cancellation_token,
             messages: chat_messages_receiver,
+            history,
         })
     }
 
+    /// Stream messages from the channel, for tests
+    #[cfg(test)]
+    pub(crate) fn from_receiver(
+        messages: mpsc::Receiver<Result<ChatMessage, ChatMessageStreamError>>,
+    ) -> Self {
+        Self {
+            cancellation_token: CancellationToken::new(),
+            messages,
+            history: Default::default(),
+        }
+    }
+
     /// Close the chat socket, causing any further calls to `next()` to return `None`.
     ///
     /// Automatically called on drop. Calling multiple times has no effect.
@@ -112,6 +173,9 @@ struct Ping {
 
     /// The last iteration that we got a Pong response to
     acknowledged: u64,
+
+    /// When to send the next ping, regardless of how many messages arrive in between
+    next: Instant,
 }
 
 impl Default for Ping {
@@ -120,6 +184,7 @@ impl Default for Ping {
             interval: DEFAULT_PING_INTERVAL,
             iteration: 0,
             acknowledged: 0,
+            next: Instant::now() + DEFAULT_PING_INTERVAL,
         }
     }
 }
@@ -134,6 +199,11 @@ struct SocketMessagesReader<R> {
         Option<oneshot::Sender<Result<(), ChatConnectError>>>,
     ),
     ping: Ping,
+
+    /// Whether to skip the next batch of chat messages, which is the history Trovo sends
+    /// after authenticating
+    skip_history: bool,
+    history: Arc<Mutex<SkippedHistory>>,
 }
 
 impl<R> SocketMessagesReader<R>
@@ -164,8 +234,9 @@ where
             _ = self.cancellation_token.cancelled() => {
                 Ok(Continuation::Stop)
             }
-            _ = sleep(self.ping.interval) => {
+            _ = sleep_until(self.ping.next) => {
                 self.ping.iteration += 1;
+                self.ping.next = Instant::now() + self.ping.interval;
 
                 // Are we missing 2 pongs?
                 if (self.ping.iteration - self.ping.acknowledged) > 2 {
@@ -202,8 +273,8 @@ where
                 let msg = serde_json::from_slice(bytes.as_slice())?;
                 Ok(self.handle_socket_message(msg).await)
             }
-            Message::Ping(_) => todo!(),
-            Message::Pong(_) => todo!(),
+            // Websocket level keepalives, tungstenite replies to pings itself
+            Message::Ping(_) | Message::Pong(_) => Ok(Continuation::Continue),
             Message::Close(reason) => Err(ChatMessageStreamError::SocketClosed(reason)),
         }
     }
@@ -232,6 +303,7 @@ where
                 if iteration > self.ping.acknowledged {
                     self.ping.acknowledged = iteration;
                     self.ping.interval = Duration::from_secs(data.gap);
+                    self.ping.next = Instant::now() + self.ping.interval;
                 }
                 Continuation::Continue
             }
@@ -239,6 +311,21 @@ where
                 channel_info: _,
                 data,
             } => {
+                if self.skip_history {
+                    self.skip_history = false;
+                    trace!(messages = data.chats.len(), "skipping chat history");
+                    let mut history = self.history.lock().unwrap();
+                    history.skipped = true;
+                    for chat in data.chats {
+                        let time = chat.send_time.timestamp();
+                        match &mut history.newest {
+                            Some((newest, _)) if time < *newest => {}
+                            Some((newest, ids)) if time == *newest => ids.push(chat.message_id),
+                            newest => *newest = Some((time, vec![chat.message_id])),
+                        }
+                    }
+                    return Continuation::Continue;
+                }
                 for chat in data.chats {
                     if self.chat_messages_sender.send(Ok(chat)).await.is_err() {
                         // Messages receiver must have been dropped and so we just need to cleanup
@@ -247,7 +334,10 @@ where
                 }
                 Continuation::Continue
             }
-            _ => unreachable!(),
+            msg => {
+                warn!(?msg, "unexpected chat socket message, ignoring...");
+                Continuation::Continue
+            }
         }
     }
 }
@@ -342,7 +432,10 @@ mod tests {
                 interval: DEFAULT_PING_INTERVAL,
                 iteration: 1,
                 acknowledged: 0,
+                next: Instant::now() + DEFAULT_PING_INTERVAL,
             },
+            skip_history: false,
+            history: Default::default(),
         };
 
         // Should acknowledge pongs
@@ -385,6 +478,136 @@ mod tests {
         assert_eq!(reader.ping.interval, DEFAULT_PING_INTERVAL);
     }
 
+    #[tokio::test]
+    async fn keepalives_are_handled() {
+        let (socket_messages_sender, mut socket_messages) = mpsc::channel(1);
+        let (chat_messages_sender, _) = mpsc::channel(CHAT_MESSAGES_BUFFER);
+        let (mut fake_sender, fake_receiver) =
+            futures::channel::mpsc::channel::<Result<Message, tungstenite::Error>>(1);
+        let mut reader = SocketMessagesReader {
+            cancellation_token: CancellationToken::new(),
+            reader: fake_receiver,
+            chat_messages_sender,
+            socket_messages_sender,
+            auth: ("authenticate".to_string(), None),
+            ping: Ping::default(),
+            skip_history: false,
+            history: Default::default(),
+        };
+
+        // Websocket pings shouldn't reach consumers or kill the reader
+        fake_sender
+            .send(Ok(Message::Ping(Vec::new())))
+            .await
+            .unwrap();
+        assert!(matches!(reader.next().await, Ok(Continuation::Continue)));
+
+        // Pings are sent when due
+        reader.ping.next = Instant::now();
+        assert!(matches!(reader.next().await, Ok(Continuation::Continue)));
+        assert!(matches!(
+            socket_messages.recv().await,
+            Some(ChatSocketMessage::Ping { nonce }) if nonce == "1"
+        ));
+        assert!(reader.ping.next > Instant::now());
+    }
+
+    #[tokio::test]
+    async fn history_is_skipped() {
+        let (socket_messages_sender, _) = mpsc::channel(1);
+        let (chat_messages_sender, mut chat_messages) = mpsc::channel(CHAT_MESSAGES_BUFFER);
+        let (mut fake_sender, fake_receiver) =
+            futures::channel::mpsc::channel::<Result<Message, tungstenite::Error>>(1);
+        let mut reader = SocketMessagesReader {
+            cancellation_token: CancellationToken::new(),
+            reader: fake_receiver,
+            chat_messages_sender,
+            socket_messages_sender,
+            auth: ("authenticate".to_string(), None),
+            ping: Ping::default(),
+            skip_history: true,
+            history: Default::default(),
+        };
+
+        // Only the first batch is history, whatever the send times
+        let history = serde_json::json!({
+            "type": "CHAT",
+            "data": {
+                "eid": "1",
+                "chats": [
+                    {"type": 0, "content": "old", "nick_name": "a", "message_id": "1", "send_time": 1600000000},
+                    {"type": 0, "content": "older", "nick_name": "a", "message_id": "2", "send_time": 1599999999},
+                    {"type": 0, "content": "old", "nick_name": "b", "message_id": "3", "send_time": 1600000000},
+                ],
+            },
+        });
+        let msg = serde_json::json!({
+            "type": "CHAT",
+            "data": {
+                "eid": "2",
+                "chats": [
+                    {"type": 0, "content": "new", "nick_name": "a", "message_id": "4", "send_time": 1599999990},
+                ],
+            },
+        });
+        let history_skipped = reader.history.clone();
+        fake_sender
+            .send(Ok(history.to_string().into()))
+            .await
+            .unwrap();
+        assert!(matches!(reader.next().await, Ok(Continuation::Continue)));
+        fake_sender.send(Ok(msg.to_string().into())).await.unwrap();
+        assert!(matches!(reader.next().await, Ok(Continuation::Continue)));
+        drop(reader);
+        let chat = chat_messages.recv().await.unwrap().unwrap();
+        assert_eq!(chat.content, "new");
+        assert!(chat_messages.recv().await.is_none());
+
+        let history_skipped = history_skipped.lock().unwrap();
+        assert!(history_skipped.skipped);
+        assert_eq!(
+            history_skipped.newest,
+            Some((1600000000, vec!["1".to_string(), "3".to_string()]))
+        );
+    }
+
+    #[tokio::test]
+    async fn unknown_message_types_are_delivered() {
+        let (socket_messages_sender, _) = mpsc::channel(1);
+        let (chat_messages_sender, mut chat_messages) = mpsc::channel(CHAT_MESSAGES_BUFFER);
+        let (mut fake_sender, fake_receiver) =
+            futures::channel::mpsc::channel::<Result<Message, tungstenite::Error>>(1);
+        let mut reader = SocketMessagesReader {
+            cancellation_token: CancellationToken::new(),
+            reader: fake_receiver,
+            chat_messages_sender,
+            socket_messages_sender,
+            auth: ("authenticate".to_string(), None),
+            ping: Ping::default(),
+            skip_history: false,
+            history: Default::default(),
+        };
+
+        let msg = serde_json::json!({
+            "type": "CHAT",
+            "data": {
+                "eid": "1",
+                "chats": [
+                    {"type": 0, "content": "hi", "nick_name": "a", "message_id": "1", "send_time": 1600000000},
+                    {"type": 6001, "content": "new", "nick_name": "a", "message_id": "2", "send_time": 1600000000},
+                ],
+            },
+        });
+        fake_sender.send(Ok(msg.to_string().into())).await.unwrap();
+        assert!(matches!(reader.next().await, Ok(Continuation::Continue)));
+        drop(reader);
+        let chat = chat_messages.recv().await.unwrap().unwrap();
+        assert_eq!(chat.content, "hi");
+        let chat = chat_messages.recv().await.unwrap().unwrap();
+        assert_eq!(chat.type_, crate::chat::ChatMessageType::Unknown(6001));
+        assert!(chat_messages.recv().await.is_none());
+    }
+
     #[test]
     fn cancel_on_drop() {
         let cancellation_token = CancellationToken::new();
@@ -394,6 +617,7 @@ mod tests {
         drop(ChatMessageStream {
             cancellation_token: cancellation_token.clone(),
             messages,
+            history: Default::default(),
         });
         assert!(cancellation_token.is_cancelled());
     }
@@ -0,0 +1,150 @@
+use crate::{time::Instant, HttpRequest, HttpResponse, Layer, Next, TransportError};
+use http::StatusCode;
+use std::{collections::HashMap, sync::Mutex, time::Duration};
+use thiserror::Error;
+
+/// [`Layer`] that stops sending requests to an endpoint after it fails repeatedly, failing fast
+/// with [`CircuitOpen`] for a cooldown period instead so that outages aren't made worse.
+///
+/// Connection errors, timeouts, rate limiting and server errors count as failures. Once
+/// `failure_threshold` requests to an endpoint fail in a row the circuit opens. After the
+/// cooldown one request is let through to test the endpoint, closing the circuit again if it
+/// succeeds.
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use trovo::{CircuitBreaker, ClientBuilder, ClientId};
+///
+/// let client = ClientBuilder::new()
+///     .with_layer(CircuitBreaker::new(5, Duration::from_secs(30)))
+///     .build(ClientId::new("client id"));
+/// ```
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    failure_threshold: u32,
+    cooldown: Duration,
+    circuits: Mutex<HashMap<String, Circuit>>,
+}
+
+#[derive(Debug, Default)]
+struct Circuit {
+    failures: u32,
+    open_until: Option<Instant>,
+    trial_running: bool,
+}
+
+/// Error returned instead of sending a request while its endpoint's circuit is open, see
+/// [`CircuitBreaker`].
+#[derive(Debug, Error)]
+#[error("circuit open for {path} after repeated failures, retry in {retry_in:?}")]
+pub struct CircuitOpen {
+    /// Path of the endpoint
+    pub path: String,
+    /// Time until a request will be let through again
+    pub retry_in: Duration,
+}
+
+impl CircuitBreaker {
+    /// Open an endpoint's circuit after `failure_threshold` consecutive failures, for `cooldown`
+    pub fn new(failure_threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            failure_threshold: failure_threshold.max(1),
+            cooldown,
+            circuits: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Check whether a request to the path can be sent
+    fn check(&self, path: &str, now: Instant) -> Result<(), CircuitOpen> {
+        let mut circuits = self.circuits.lock().unwrap();
+        let circuit = match circuits.get_mut(path) {
+            Some(circuit) => circuit,
+            None => return Ok(()),
+        };
+        match circuit.open_until {
+            Some(open_until) if now < open_until => Err(CircuitOpen {
+                path: path.to_string(),
+                retry_in: open_until - now,
+            }),
+            Some(_) => {
+                // Keep the circuit open while the trial request runs, in case it never finishes
+                circuit.open_until = Some(now + self.cooldown);
+                circuit.trial_running = true;
+                Ok(())
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Record the outcome of a request to the path
+    fn record(&self, path: &str, failed: bool, now: Instant) {
+        let mut circuits = self.circuits.lock().unwrap();
+        if !failed {
+            circuits.remove(path);
+            return;
+        }
+        let circuit = circuits.entry(path.to_string()).or_default();
+        circuit.failures += 1;
+        if circuit.trial_running || circuit.failures >= self.failure_threshold {
+            if circuit.open_until.is_none() || circuit.trial_running {
+                warn!(path, cooldown = ?self.cooldown, "opening circuit after repeated failures");
+            }
+            circuit.open_until = Some(now + self.cooldown);
+            circuit.trial_running = false;
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Layer for CircuitBreaker {
+    async fn handle(
+        &self,
+        request: HttpRequest,
+        next: Next<'_>,
+    ) -> Result<HttpResponse, TransportError> {
+        let path = request.uri().path().to_string();
+        self.check(&path, Instant::now())
+            .map_err(TransportError::new)?;
+
+        let res = next.run(request).await;
+        let failed = match &res {
+            Ok(res) => {
+                res.status().is_server_error() || res.status() == StatusCode::TOO_MANY_REQUESTS
+            }
+            Err(_) => true,
+        };
+        self.record(&path, failed, Instant::now());
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opens_after_threshold_and_recovers() {
+        let breaker = CircuitBreaker::new(2, Duration::from_secs(10));
+        let start = Instant::now();
+        let path = "/openplatform/getusers";
+
+        breaker.record(path, true, start);
+        assert!(breaker.check(path, start).is_ok());
+        breaker.record(path, true, start);
+        let err = breaker.check(path, start).unwrap_err();
+        assert_eq!(err.retry_in, Duration::from_secs(10));
+        assert!(breaker.check("/openplatform/channels/id", start).is_ok());
+
+        // one trial request after the cooldown, failing reopens the circuit
+        let later = start + Duration::from_secs(10);
+        assert!(breaker.check(path, later).is_ok());
+        assert!(breaker.check(path, later).is_err());
+        breaker.record(path, true, later);
+        assert!(breaker.check(path, later + Duration::from_secs(5)).is_err());
+
+        let later = later + Duration::from_secs(10);
+        assert!(breaker.check(path, later).is_ok());
+        breaker.record(path, false, later);
+        assert!(breaker.check(path, later).is_ok());
+    }
+}
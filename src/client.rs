@@ -1,17 +1,58 @@
 use crate::{
-    access_token, AccessTokenProvider, ApiError, AuthenticatedRequestError, ChannelInfo,
-    ChannelUpdate, ChannelUpdatePayload, ClientIdProvider, EmoteChannels, EmoteFetchType,
-    ErrorStatus, GetChannelByIdPayload, GetEmotesPayload, GetEmotesResponse, GetUsersPayload,
-    GetUsersResponse, RequestError, User,
+    access_token,
+    rate_limit::{RateLimitCategory, RateLimiter},
+    response::parse_with_meta,
+    retry::{clone_request, NotIdempotent},
+    time,
+    transport::{self, BuilderExt},
+    AccessToken, AccessTokenProvider, AuthenticatedRequestError, CategoriesResponse, Category,
+    ChannelInfo, ChannelSnapshot, ChannelUpdate, ChannelUpdatePayload, ClientBuilder, ClientHooks,
+    ClientIdProvider, Clips, ClipsPayload, ClipsQuery, EmoteChannels, EmoteFetchType, ErrorStatus,
+    Followers, FollowersQuery, GetChannelByIdPayload, GetChannelByUsernamePayload,
+    GetEmotesPayload, GetEmotesResponse, GetUsersPayload, GetUsersResponse, HttpRequest,
+    HttpResponse, HttpTransport, LiveStatus, PastStreams, PastStreamsPayload, PastStreamsQuery,
+    RequestError, RequestInfo, RequestOutcome, ResponseMeta, RetryPolicy, RevokeTokenPayload,
+    Scope, SearchCategoryPayload, Secret, StreamUrl, StreamUrlsResponse, Subscriber, Subscribers,
+    SubscribersQuery, TokenInfo, TopChannels, TopChannelsQuery, TransportError, User,
+    UserChannelInfo, Viewers, ViewersQuery, WithMeta,
 };
-use reqwest::header;
-use std::time::Duration;
+use chrono::Utc;
+use http::{
+    header::{self, HeaderValue},
+    request::Builder,
+    Method, StatusCode,
+};
+use serde::Serialize;
+use std::{sync::Arc, time::Duration};
+
+pub(crate) const DEFAULT_REFRESH_MARGIN: Duration = Duration::from_secs(60);
+
+/// Base url of the Trovo open platform api
+pub(crate) const DEFAULT_BASE_URL: &str = "https://open-api.trovo.live/openplatform";
 
 /// Entrypoint for making requests to the Trovo api.
+///
+/// Use [`ClientBuilder`] to configure the client beyond the defaults.
+///
+/// Cloning a client is cheap and clones share the same transport, and so the same connection
+/// pool, as well as the rate limits. Clone the client into each task rather than building a
+/// new one. Stateful auth providers such as
+/// [`RefreshingTokenProvider`](crate::RefreshingTokenProvider) aren't [`Clone`], wrap them in a
+/// [`SharedAuth`](crate::SharedAuth) or [`Arc`] so clones share the same token too.
 #[derive(Debug, Clone)]
 pub struct Client<A> {
-    pub(crate) http: reqwest::Client,
+    pub(crate) http: Arc<dyn HttpTransport>,
     pub(crate) auth_provider: A,
+    pub(crate) refresh_margin: Duration,
+    pub(crate) base_url: Arc<str>,
+    pub(crate) timeout: Option<Duration>,
+    pub(crate) proxy: Option<Arc<url::Url>>,
+    pub(crate) retry: Option<RetryPolicy>,
+    pub(crate) rate_limiter: Option<Arc<RateLimiter>>,
+    pub(crate) user_agent: HeaderValue,
+    pub(crate) hooks: Option<Arc<dyn ClientHooks>>,
+    #[cfg(feature = "chat")]
+    pub(crate) chat: crate::chat::ChatConfig,
 }
 
 impl<A> Client<A> {
@@ -24,13 +65,7 @@ impl<A> Client<A> {
     ///
     /// This method panics if a TLS backend cannot be initialized, or the resolver cannot load the system configuration.
     pub fn new(auth_provider: A) -> Self {
-        Self {
-            http: reqwest::Client::builder()
-                .timeout(Duration::from_secs(30))
-                .build()
-                .unwrap(),
-            auth_provider,
-        }
+        ClientBuilder::new().build(auth_provider)
     }
 
     /// Creates a new trovo client using the provided reqwest client.
@@ -38,49 +73,234 @@ impl<A> Client<A> {
     /// This can be useful to allow sharing of a reqwest client's connection pool
     /// across your program
     pub fn from_reqwest(http: reqwest::Client, auth_provider: A) -> Self {
-        Self {
-            http,
+        Self::with_transport(http, auth_provider)
+    }
+
+    /// Creates a new trovo client that sends its requests through the given transport.
+    pub fn with_transport(http: impl HttpTransport + 'static, auth_provider: A) -> Self {
+        ClientBuilder::new().transport(http).build(auth_provider)
+    }
+
+    /// Sets how long before an [`AccessToken::ExpiresAt`] token expires that it should be
+    /// refreshed. Defaults to 60 seconds.
+    pub fn with_refresh_margin(mut self, refresh_margin: Duration) -> Self {
+        self.refresh_margin = refresh_margin;
+        self
+    }
+
+    /// Sets the base url api requests are sent to, such as to point the client at a mock server
+    /// in tests. Defaults to `https://open-api.trovo.live/openplatform`.
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').into();
+        self
+    }
+
+    /// Sets the overall timeout for requests, replacing the one set on the [`ClientBuilder`].
+    ///
+    /// Clients are cheap to clone, so this can be used to change the timeout of a single call:
+    ///
+    /// ```no_run
+    /// # async fn run(client: trovo::Client<trovo::ClientId>) {
+    /// use std::time::Duration;
+    ///
+    /// let users = client
+    ///     .clone()
+    ///     .with_timeout(Duration::from_secs(5))
+    ///     .users(vec!["username".to_string()])
+    ///     .await;
+    /// # }
+    /// ```
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Create a client with the same configuration, connection pool and rate limits but a
+    /// different auth provider, so that one client can make calls on behalf of many users.
+    ///
+    /// ```no_run
+    /// # async fn run(client: trovo::Client<trovo::ClientId>) -> Result<(), Box<dyn std::error::Error>> {
+    /// use trovo::AccessTokenOnly;
+    ///
+    /// let user = client.as_user(AccessTokenOnly::new("client id", "user's access token"));
+    /// let info = user.validate_token().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Use an [`AuthRegistry`](crate::AuthRegistry) to keep track of the auth providers of many
+    /// users.
+    pub fn as_user<B>(&self, auth_provider: B) -> Client<B> {
+        Client {
+            http: self.http.clone(),
             auth_provider,
+            refresh_margin: self.refresh_margin,
+            base_url: self.base_url.clone(),
+            timeout: self.timeout,
+            proxy: self.proxy.clone(),
+            retry: self.retry.clone(),
+            rate_limiter: self.rate_limiter.clone(),
+            user_agent: self.user_agent.clone(),
+            hooks: self.hooks.clone(),
+            #[cfg(feature = "chat")]
+            chat: self.chat.clone(),
+        }
+    }
+
+    /// Url of the given api endpoint
+    pub(crate) fn url(&self, path: &str) -> String {
+        format!("{}/{}", self.base_url, path)
+    }
+
+    /// Send a request through the client's transport
+    pub(crate) async fn send(&self, request: HttpRequest) -> Result<HttpResponse, RequestError> {
+        let policy = match &self.retry {
+            Some(policy) => policy,
+            None => return self.send_once(request, 1).await.0,
+        };
+
+        let idempotent = request.extensions().get::<NotIdempotent>().is_none();
+        let mut retry = 0;
+        loop {
+            let (res, info) = self.send_once(clone_request(&request), retry + 1).await;
+            match res {
+                Err(err)
+                    if err.is_retryable()
+                        && (idempotent || err.is_unhandled())
+                        && retry + 1 < policy.max_attempts() =>
+                {
+                    let backoff = policy.backoff(retry);
+                    debug!(
+                        %err,
+                        ?backoff,
+                        uri = %request.uri(),
+                        request_id = ?err.request_id(),
+                        "retrying failed request"
+                    );
+                    if let (Some(hooks), Some(info)) = (&self.hooks, &info) {
+                        hooks.on_retry(info, &err, backoff);
+                    }
+                    time::sleep(backoff).await;
+                    retry += 1;
+                }
+                res => return res,
+            }
         }
     }
+
+    /// Send one attempt at a request, returning the info passed to the hooks if there are any
+    async fn send_once(
+        &self,
+        mut request: HttpRequest,
+        attempt: u32,
+    ) -> (Result<HttpResponse, RequestError>, Option<RequestInfo>) {
+        if let Some(rate_limiter) = &self.rate_limiter {
+            let category = RateLimitCategory::for_path(request.uri().path());
+            rate_limiter.acquire(category).await;
+        }
+        let request_id = transport::request_id(&mut request);
+        let info = self.hooks.as_ref().map(|hooks| {
+            let info = RequestInfo {
+                method: request.method().clone(),
+                path: request.uri().path().to_string(),
+                request_id: request_id.clone(),
+                attempt,
+            };
+            hooks.on_request_start(&info);
+            info
+        });
+        let started = time::Instant::now();
+
+        let send = transport::send(&*self.http, request);
+        let res = match self.timeout {
+            Some(timeout) => time::timeout(timeout, send)
+                .await
+                .unwrap_or_else(|elapsed| {
+                    Err(RequestError::Transport(
+                        TransportError::new(elapsed).with_request_id(&request_id),
+                    ))
+                }),
+            None => send.await,
+        };
+
+        if let (Some(hooks), Some(info)) = (&self.hooks, &info) {
+            let status = match &res {
+                Ok(res) => Some(res.status()),
+                Err(RequestError::Status(status)) => Some(*status),
+                Err(_) => None,
+            };
+            hooks.on_request_end(
+                info,
+                &RequestOutcome {
+                    status,
+                    duration: started.elapsed(),
+                    error: res.as_ref().err(),
+                },
+            );
+        }
+        (res, info)
+    }
 }
 
 impl<A> Client<A>
 where
     A: ClientIdProvider,
 {
+    /// Start building a request to the given api endpoint
+    pub(crate) fn request(&self, method: Method, path: &str) -> Builder {
+        transport::request(method, &self.url(path), self.auth_provider.client_id())
+            .header(header::USER_AGENT, self.user_agent.clone())
+    }
+
     /// Gets a list of user’s channel id, user id, and nickname, by usernames.
     ///
     /// Note: Even if just one of the usernames doesn't exist, the result will be
     /// an empty vec due to api limitations.
-    pub async fn users(&self, usernames: Vec<String>) -> Result<Vec<User>, RequestError> {
-        let res = self
-            .http
-            .post("https://open-api.trovo.live/openplatform/getusers")
-            .header("Client-ID", self.auth_provider.client_id())
-            .json(&GetUsersPayload { user: usernames })
-            .send()
-            .await?;
-
-        if ApiError::can_handle_code(res.status()) {
-            let err: ApiError = res.json().await.unwrap_or_default();
-
-            if err.status == ErrorStatus::InvalidParameters {
-                return Ok(vec![]);
-            } else {
-                return Err(RequestError::ApiError(err));
+    ///
+    /// ```no_run
+    /// # async fn run(client: trovo::Client<trovo::ClientId>) -> Result<(), trovo::RequestError> {
+    /// for user in client.users(["username", "other_user"]).await? {
+    ///     println!("{} has user id {}", user.nickname, user.user_id);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "get_users")]
+    pub async fn users(
+        &self,
+        usernames: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Result<Vec<User>, RequestError> {
+        match self.users_with_meta(usernames).await {
+            Err(RequestError::ApiError(err)) if err.status == ErrorStatus::InvalidParameters => {
+                Ok(vec![])
             }
+            res => Ok(res?.data),
         }
+    }
+
+    /// Like [`Client::users`], but also returns the response's metadata.
+    ///
+    /// If any of the usernames doesn't exist this returns the api's
+    /// [`InvalidParameters`](ErrorStatus::InvalidParameters) error rather than an empty vec.
+    pub async fn users_with_meta(
+        &self,
+        usernames: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Result<WithMeta<Vec<User>>, RequestError> {
+        let req = self
+            .request(Method::POST, "getusers")
+            .json(&GetUsersPayload {
+                user: usernames.into_iter().map(Into::into).collect(),
+            })?;
 
-        let response: GetUsersResponse = res.error_for_status()?.json().await?;
-        Ok(response.users)
+        let response: WithMeta<GetUsersResponse> = parse_with_meta(self.send(req).await?)?;
+        Ok(response.map(|response| response.users))
     }
 
     /// Gets a user’s channel id, user id, and nickname, by username.
     ///
     /// Returns None if the user was not found
     pub async fn user(&self, username: impl Into<String>) -> Result<Option<User>, RequestError> {
-        let mut users = self.users(vec![username.into()]).await?;
+        let mut users = self.users([username.into()]).await?;
 
         if !users.is_empty() {
             Ok(Some(users.remove(0)))
@@ -92,59 +312,396 @@ where
     /// Gets channel information for the given id
     ///
     /// Returns None if the channel was not found
+    #[doc(alias = "channel_info_by_id")]
     pub async fn channel_by_id(
         &self,
         channel_id: impl Into<String>,
     ) -> Result<Option<ChannelInfo>, RequestError> {
-        let res = self
-            .http
-            .post("https://open-api.trovo.live/openplatform/channels/id")
-            .header("Client-ID", self.auth_provider.client_id())
+        Ok(self.channel_by_id_with_meta(channel_id).await?.data)
+    }
+
+    /// Like [`Client::channel_by_id`], but also returns the response's metadata.
+    pub async fn channel_by_id_with_meta(
+        &self,
+        channel_id: impl Into<String>,
+    ) -> Result<WithMeta<Option<ChannelInfo>>, RequestError> {
+        self.channel_with_meta(&GetChannelByIdPayload {
+            channel_id: channel_id.into(),
+        })
+        .await
+    }
+
+    /// Gets channel information for the given username
+    ///
+    /// Returns None if the channel was not found
+    #[doc(alias = "channel_info_by_username")]
+    pub async fn channel_by_username(
+        &self,
+        username: impl Into<String>,
+    ) -> Result<Option<ChannelInfo>, RequestError> {
+        Ok(self.channel_by_username_with_meta(username).await?.data)
+    }
+
+    /// Like [`Client::channel_by_username`], but also returns the response's metadata.
+    pub async fn channel_by_username_with_meta(
+        &self,
+        username: impl Into<String>,
+    ) -> Result<WithMeta<Option<ChannelInfo>>, RequestError> {
+        self.channel_with_meta(&GetChannelByUsernamePayload {
+            username: username.into(),
+        })
+        .await
+    }
+
+    /// Gets whether a channel is live, with its title, category and viewers.
+    ///
+    /// Returns None if the channel was not found
+    pub async fn is_live(
+        &self,
+        channel_id: impl Into<String>,
+    ) -> Result<Option<LiveStatus>, RequestError> {
+        Ok(self.channel_by_id(channel_id).await?.map(LiveStatus::from))
+    }
+
+    /// Gets a channel's info along with its follower, subscriber and viewer counts. Once the
+    /// channel is found, its followers and viewers are fetched concurrently, skipping the viewers
+    /// of channels that aren't live.
+    ///
+    /// Returns None if the channel was not found. The requests go through the client's rate
+    /// limits like any others.
+    pub async fn channel_snapshot(
+        &self,
+        channel_id: impl Into<String>,
+    ) -> Result<Option<ChannelSnapshot>, RequestError> {
+        let channel_id = channel_id.into();
+        let info = match self.channel_by_id(channel_id.clone()).await? {
+            Some(info) => info,
+            None => return Ok(None),
+        };
+        let followers_query = FollowersQuery {
+            limit: Some(1),
+            ..Default::default()
+        };
+        let viewers_query = ViewersQuery {
+            limit: Some(1),
+            ..Default::default()
+        };
+        let viewers = async {
+            if info.is_live {
+                Ok(self
+                    .channel_viewers(&channel_id, &viewers_query)
+                    .await?
+                    .total)
+            } else {
+                Ok(0)
+            }
+        };
+        let (followers, viewers) = futures::try_join!(
+            self.channel_followers(&channel_id, &followers_query),
+            viewers,
+        )?;
+        Ok(Some(ChannelSnapshot {
+            followers: followers.total,
+            subscribers: info.subscriber_num,
+            viewers,
+            info,
+        }))
+    }
+
+    async fn channel_with_meta(
+        &self,
+        payload: &impl Serialize,
+    ) -> Result<WithMeta<Option<ChannelInfo>>, RequestError> {
+        let req = self.request(Method::POST, "channels/id").json(payload)?;
+
+        let response: WithMeta<ChannelInfo> = parse_with_meta(self.send(req).await?)?;
+        Ok(response.map(|channel| {
+            if channel.username.is_empty() {
+                // Trovo api returns a nulled out channel if it can't be found, username is
+                // probably never legitimately blank
+                None
+            } else {
+                Some(channel)
+            }
+        }))
+    }
+
+    /// Gets a page of the channels that are live right now, with the most viewers first.
+    ///
+    /// ```no_run
+    /// # async fn run(client: trovo::Client<trovo::ClientId>) -> Result<(), trovo::RequestError> {
+    /// use trovo::TopChannelsQuery;
+    ///
+    /// let mut query = TopChannelsQuery {
+    ///     limit: Some(100),
+    ///     ..Default::default()
+    /// };
+    /// loop {
+    ///     let page = client.top_channels(&query).await?;
+    ///     for channel in &page.channels {
+    ///         println!("{} has {} viewers", channel.username, channel.current_viewers);
+    ///     }
+    ///     match page.next_page(&query) {
+    ///         Some(next) => query = next,
+    ///         None => break,
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "gettopchannels")]
+    pub async fn top_channels(
+        &self,
+        query: &TopChannelsQuery,
+    ) -> Result<TopChannels, RequestError> {
+        Ok(self.top_channels_with_meta(query).await?.data)
+    }
+
+    /// Like [`Client::top_channels`], but also returns the response's metadata.
+    pub async fn top_channels_with_meta(
+        &self,
+        query: &TopChannelsQuery,
+    ) -> Result<WithMeta<TopChannels>, RequestError> {
+        let req = self.request(Method::POST, "gettopchannels").json(query)?;
+        parse_with_meta(self.send(req).await?)
+    }
+
+    /// Gets every category on Trovo, with the most popular first.
+    ///
+    /// The api returns the whole list at once rather than in pages. It rarely changes, so
+    /// consider caching it, such as with a [`ResponseCache`](crate::ResponseCache).
+    #[doc(alias = "top_categories")]
+    pub async fn categories(&self) -> Result<Vec<Category>, RequestError> {
+        Ok(self.categories_with_meta().await?.data)
+    }
+
+    /// Like [`Client::categories`], but also returns the response's metadata.
+    pub async fn categories_with_meta(&self) -> Result<WithMeta<Vec<Category>>, RequestError> {
+        let req = self.request(Method::GET, "categorys/top").empty()?;
+
+        let response: WithMeta<CategoriesResponse> = parse_with_meta(self.send(req).await?)?;
+        Ok(response.map(|response| response.category_info))
+    }
+
+    /// Gets a category by its id, such as [`ChannelInfo::category_id`].
+    ///
+    /// Returns None if the category was not found. The api can't look up a single category, so
+    /// this gets the whole list with [`Client::categories`]. To resolve many ids, get the list
+    /// once instead.
+    pub async fn category(
+        &self,
+        category_id: impl AsRef<str>,
+    ) -> Result<Option<Category>, RequestError> {
+        let categories = self.categories().await?;
+        Ok(categories
+            .into_iter()
+            .find(|category| category.id == category_id.as_ref()))
+    }
+
+    /// Searches for categories by name, returning at most `limit` results if given.
+    ///
+    /// ```no_run
+    /// # async fn run(client: trovo::Client<trovo::ClientId>) -> Result<(), trovo::RequestError> {
+    /// if let Some(category) = client.search_categories("minecraft", Some(1)).await?.first() {
+    ///     println!("{} has id {}", category.name, category.id);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn search_categories(
+        &self,
+        query: impl Into<String>,
+        limit: Option<u32>,
+    ) -> Result<Vec<Category>, RequestError> {
+        Ok(self.search_categories_with_meta(query, limit).await?.data)
+    }
+
+    /// Like [`Client::search_categories`], but also returns the response's metadata.
+    pub async fn search_categories_with_meta(
+        &self,
+        query: impl Into<String>,
+        limit: Option<u32>,
+    ) -> Result<WithMeta<Vec<Category>>, RequestError> {
+        let req = self
+            .request(Method::POST, "searchcategory")
+            .json(&SearchCategoryPayload {
+                query: query.into(),
+                limit,
+            })?;
+
+        let response: WithMeta<CategoriesResponse> = parse_with_meta(self.send(req).await?)?;
+        Ok(response.map(|response| response.category_info))
+    }
+
+    /// Gets a page of the users following a channel, and the channel's follower count.
+    ///
+    /// ```no_run
+    /// # async fn run(client: trovo::Client<trovo::ClientId>) -> Result<(), trovo::RequestError> {
+    /// use trovo::FollowersQuery;
+    ///
+    /// let mut query = FollowersQuery {
+    ///     limit: Some(100),
+    ///     ..Default::default()
+    /// };
+    /// let mut followers = Vec::new();
+    /// loop {
+    ///     let page = client.channel_followers("channel id", &query).await?;
+    ///     let next = page.next_page(&query);
+    ///     followers.extend(page.followers);
+    ///     match next {
+    ///         Some(next) => query = next,
+    ///         None => break,
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn channel_followers(
+        &self,
+        channel_id: impl AsRef<str>,
+        query: &FollowersQuery,
+    ) -> Result<Followers, RequestError> {
+        Ok(self
+            .channel_followers_with_meta(channel_id, query)
+            .await?
+            .data)
+    }
+
+    /// Like [`Client::channel_followers`], but also returns the response's metadata.
+    pub async fn channel_followers_with_meta(
+        &self,
+        channel_id: impl AsRef<str>,
+        query: &FollowersQuery,
+    ) -> Result<WithMeta<Followers>, RequestError> {
+        let path = format!("channels/{}/followers", channel_id.as_ref());
+        let req = self.request(Method::POST, &path).json(query)?;
+        parse_with_meta(self.send(req).await?)
+    }
+
+    /// Gets a page of the viewers watching a live channel, grouped by their roles.
+    ///
+    /// Page through all of the viewers with [`Viewers::next_page`].
+    pub async fn channel_viewers(
+        &self,
+        channel_id: impl AsRef<str>,
+        query: &ViewersQuery,
+    ) -> Result<Viewers, RequestError> {
+        Ok(self
+            .channel_viewers_with_meta(channel_id, query)
+            .await?
+            .data)
+    }
+
+    /// Like [`Client::channel_viewers`], but also returns the response's metadata.
+    pub async fn channel_viewers_with_meta(
+        &self,
+        channel_id: impl AsRef<str>,
+        query: &ViewersQuery,
+    ) -> Result<WithMeta<Viewers>, RequestError> {
+        let path = format!("channels/{}/viewers", channel_id.as_ref());
+        let req = self.request(Method::POST, &path).json(query)?;
+        parse_with_meta(self.send(req).await?)
+    }
+
+    /// Gets the m3u8 playback urls of a live channel, one for each quality.
+    #[doc(alias = "livestreamurl")]
+    pub async fn stream_urls(
+        &self,
+        channel_id: impl Into<String>,
+    ) -> Result<Vec<StreamUrl>, RequestError> {
+        Ok(self.stream_urls_with_meta(channel_id).await?.data)
+    }
+
+    /// Like [`Client::stream_urls`], but also returns the response's metadata.
+    pub async fn stream_urls_with_meta(
+        &self,
+        channel_id: impl Into<String>,
+    ) -> Result<WithMeta<Vec<StreamUrl>>, RequestError> {
+        let req = self
+            .request(Method::POST, "livestreamurl")
             .json(&GetChannelByIdPayload {
                 channel_id: channel_id.into(),
-            })
-            .send()
-            .await?;
+            })?;
 
-        if ApiError::can_handle_code(res.status()) {
-            let err: ApiError = res.json().await.unwrap_or_default();
-            return Err(RequestError::ApiError(err));
-        }
+        let response: WithMeta<StreamUrlsResponse> = parse_with_meta(self.send(req).await?)?;
+        Ok(response.map(|response| response.stream_urls))
+    }
 
-        let channel: ChannelInfo = res.error_for_status()?.json().await?;
-        Ok(if channel.username.is_empty() {
-            // Trovo api returns a nulled out channel if it can't be found, username is probably
-            // never legitimately blank
-            None
-        } else {
-            Some(channel)
-        })
+    /// Gets a page of a channel's clips, optionally filtered by category and period.
+    ///
+    /// Page through all of the clips with [`Clips::next_page`].
+    pub async fn clips(
+        &self,
+        channel_id: impl Into<String>,
+        query: &ClipsQuery,
+    ) -> Result<Clips, RequestError> {
+        Ok(self.clips_with_meta(channel_id, query).await?.data)
+    }
+
+    /// Like [`Client::clips`], but also returns the response's metadata.
+    pub async fn clips_with_meta(
+        &self,
+        channel_id: impl Into<String>,
+        query: &ClipsQuery,
+    ) -> Result<WithMeta<Clips>, RequestError> {
+        let req = self.request(Method::POST, "clips").json(&ClipsPayload {
+            channel_id: channel_id.into(),
+            query: query.clone(),
+        })?;
+        parse_with_meta(self.send(req).await?)
+    }
+
+    /// Gets a page of a channel's past stream recordings, optionally filtered by category and
+    /// period.
+    ///
+    /// Page through all of the streams with [`PastStreams::next_page`].
+    pub async fn past_streams(
+        &self,
+        channel_id: impl Into<String>,
+        query: &PastStreamsQuery,
+    ) -> Result<PastStreams, RequestError> {
+        Ok(self.past_streams_with_meta(channel_id, query).await?.data)
+    }
+
+    /// Like [`Client::past_streams`], but also returns the response's metadata.
+    pub async fn past_streams_with_meta(
+        &self,
+        channel_id: impl Into<String>,
+        query: &PastStreamsQuery,
+    ) -> Result<WithMeta<PastStreams>, RequestError> {
+        let req = self
+            .request(Method::POST, "paststreams")
+            .json(&PastStreamsPayload {
+                channel_id: channel_id.into(),
+                query: query.clone(),
+            })?;
+        parse_with_meta(self.send(req).await?)
     }
 
     /// Gets list of emotes for each channel and global/event emotes if requested.
     pub async fn emotes(
         &self,
         emote_type: EmoteFetchType,
-        channel_ids: Vec<String>,
+        channel_ids: impl IntoIterator<Item = impl Into<String>>,
     ) -> Result<EmoteChannels, RequestError> {
-        let res = self
-            .http
-            .post("https://open-api.trovo.live/openplatform/getemotes")
-            .header("Client-ID", self.auth_provider.client_id())
+        Ok(self.emotes_with_meta(emote_type, channel_ids).await?.data)
+    }
+
+    /// Like [`Client::emotes`], but also returns the response's metadata.
+    pub async fn emotes_with_meta(
+        &self,
+        emote_type: EmoteFetchType,
+        channel_ids: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Result<WithMeta<EmoteChannels>, RequestError> {
+        let req = self
+            .request(Method::POST, "getemotes")
             .json(&GetEmotesPayload {
                 emote_type,
-                channel_id: channel_ids,
-            })
-            .send()
-            .await?;
-
-        if ApiError::can_handle_code(res.status()) {
-            let err: ApiError = res.json().await.unwrap_or_default();
-            return Err(RequestError::ApiError(err));
-        }
+                channel_id: channel_ids.into_iter().map(Into::into).collect(),
+            })?;
 
-        let response: GetEmotesResponse = res.error_for_status()?.json().await?;
-        Ok(response.channels)
+        let response: WithMeta<GetEmotesResponse> = parse_with_meta(self.send(req).await?)?;
+        Ok(response.map(|response| response.channels))
     }
 }
 
@@ -152,37 +709,580 @@ impl<A> Client<A>
 where
     A: AccessTokenProvider,
 {
+    /// Get the auth provider's access token, refreshing it if it has expired or is about to.
+    pub(crate) async fn access_token(&self) -> Result<String, A::Error> {
+        let margin = chrono::Duration::from_std(self.refresh_margin)
+            .unwrap_or_else(|_| chrono::Duration::zero());
+        match self.auth_provider.access_token() {
+            AccessToken::Token(token) => Ok(token),
+            AccessToken::ExpiresAt(token, expires_at) if expires_at - margin > Utc::now() => {
+                Ok(token)
+            }
+            AccessToken::ExpiresAt(..) | AccessToken::NeedsRefresh => {
+                self.auth_provider.refresh_token().await
+            }
+        }
+    }
+
+    /// Check that the auth provider's access token has the given scope, if its scopes are known.
+    pub(crate) fn require_scope(
+        &self,
+        scope: Scope,
+    ) -> Result<(), AuthenticatedRequestError<A::Error>> {
+        match self.auth_provider.scopes() {
+            Some(scopes) if !scopes.contains(scope) => {
+                Err(AuthenticatedRequestError::MissingScope(scope))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Send a request authenticated with the auth provider's access token.
+    ///
+    /// If the api rejects the token, it is refreshed and the request sent once more.
+    pub(crate) async fn send_authenticated(
+        &self,
+        mut request: HttpRequest,
+    ) -> Result<HttpResponse, AuthenticatedRequestError<A::Error>> {
+        let token = access_token!(self, AuthenticatedRequestError);
+        set_access_token(&mut request, &token)?;
+
+        match self.send(clone_request(&request)).await {
+            Err(err) if is_token_rejected(&err) => {
+                debug!(%err, "access token rejected, refreshing and retrying");
+                let token = self
+                    .auth_provider
+                    .refresh_token()
+                    .await
+                    .map_err(AuthenticatedRequestError::RefreshToken)?;
+                set_access_token(&mut request, &token)?;
+                Ok(self.send(request).await?)
+            }
+            res => Ok(res?),
+        }
+    }
+
+    /// Validate the auth provider's access token, returning the user, scopes and expiry
+    /// associated with it.
+    pub async fn validate_token(&self) -> Result<TokenInfo, AuthenticatedRequestError<A::Error>> {
+        Ok(self.validate_token_with_meta().await?.data)
+    }
+
+    /// Like [`Client::validate_token`], but also returns the response's metadata.
+    pub async fn validate_token_with_meta(
+        &self,
+    ) -> Result<WithMeta<TokenInfo>, AuthenticatedRequestError<A::Error>> {
+        let req = self.request(Method::GET, "validate").empty()?;
+        Ok(parse_with_meta(self.send_authenticated(req).await?)?)
+    }
+
+    /// Revoke the auth provider's access token. Any further authenticated calls will fail until
+    /// the user authorizes your app again.
+    pub async fn revoke_token(&self) -> Result<(), AuthenticatedRequestError<A::Error>> {
+        let req = self
+            .request(Method::POST, "revoke")
+            .json(&RevokeTokenPayload {
+                access_token: Secret::new(access_token!(self, AuthenticatedRequestError)),
+            })?;
+
+        self.send(req).await?;
+        Ok(())
+    }
+
+    /// Get the authenticated user's own channel, including its stream key.
+    ///
+    /// # Scopes
+    ///
+    /// Requires `channel_details_self`
+    pub async fn channel_for_user(
+        &self,
+    ) -> Result<UserChannelInfo, AuthenticatedRequestError<A::Error>> {
+        Ok(self.channel_for_user_with_meta().await?.data)
+    }
+
+    /// Like [`Client::channel_for_user`], but also returns the response's metadata.
+    pub async fn channel_for_user_with_meta(
+        &self,
+    ) -> Result<WithMeta<UserChannelInfo>, AuthenticatedRequestError<A::Error>> {
+        self.require_scope(Scope::ChannelDetailsSelf)?;
+        let req = self.request(Method::GET, "channel").empty()?;
+        Ok(parse_with_meta(self.send_authenticated(req).await?)?)
+    }
+
+    /// Gets a page of the users subscribed to a channel, and the channel's subscriber count.
+    ///
+    /// Page through all of the subscribers with [`Subscribers::next_page`].
+    ///
+    /// # Scopes
+    ///
+    /// Requires `channel_subscriptions` of the channel's owner
+    pub async fn channel_subscribers(
+        &self,
+        channel_id: impl AsRef<str>,
+        query: &SubscribersQuery,
+    ) -> Result<Subscribers, AuthenticatedRequestError<A::Error>> {
+        Ok(self
+            .channel_subscribers_with_meta(channel_id, query)
+            .await?
+            .data)
+    }
+
+    /// Like [`Client::channel_subscribers`], but also returns the response's metadata.
+    pub async fn channel_subscribers_with_meta(
+        &self,
+        channel_id: impl AsRef<str>,
+        query: &SubscribersQuery,
+    ) -> Result<WithMeta<Subscribers>, AuthenticatedRequestError<A::Error>> {
+        self.require_scope(Scope::ChannelSubscriptions)?;
+        let mut path = format!("channels/{}/subscriptions", channel_id.as_ref());
+        let pairs = query.to_pairs();
+        if !pairs.is_empty() {
+            path.push('?');
+            path.push_str(
+                &url::form_urlencoded::Serializer::new(String::new())
+                    .extend_pairs(pairs)
+                    .finish(),
+            );
+        }
+        let req = self.request(Method::GET, &path).empty()?;
+        Ok(parse_with_meta(self.send_authenticated(req).await?)?)
+    }
+
+    /// Find a user's subscription to a channel, or None if they aren't subscribed.
+    ///
+    /// The api can't look up a single subscriber, so this pages through the channel's
+    /// subscribers until the user is found. For many lookups, list the subscribers once with
+    /// [`Client::channel_subscribers`] instead.
+    ///
+    /// # Scopes
+    ///
+    /// Requires `channel_subscriptions` of the channel's owner
+    pub async fn subscription_status(
+        &self,
+        channel_id: impl AsRef<str>,
+        user_id: impl AsRef<str>,
+    ) -> Result<Option<Subscriber>, AuthenticatedRequestError<A::Error>> {
+        let mut query = SubscribersQuery {
+            limit: Some(100),
+            ..Default::default()
+        };
+        loop {
+            let page = self
+                .channel_subscribers(channel_id.as_ref(), &query)
+                .await?;
+            let next = page.next_page(&query);
+            let found = page
+                .subscribers
+                .into_iter()
+                .find(|subscriber| subscriber.user.user_id == user_id.as_ref());
+            match (found, next) {
+                (Some(subscriber), _) => return Ok(Some(subscriber)),
+                (None, Some(next)) => query = next,
+                (None, None) => return Ok(None),
+            }
+        }
+    }
+
     /// Allows you to update the user’s channel settings, including title, category, language,
-    /// audience type. You may update only part of the info.
+    /// audience type. You may update only part of the info, fields left as None in the update
+    /// aren't changed.
+    ///
+    /// # Scopes
+    ///
+    /// Requires `channel_update_self`
+    #[doc(alias = "edit_channel")]
     pub async fn update_channel(
         &self,
         channel_id: impl Into<String>,
         update: ChannelUpdate,
     ) -> Result<(), AuthenticatedRequestError<A::Error>> {
-        let res = self
-            .http
-            .post("https://open-api.trovo.live/openplatform/channels/update")
-            .header("Client-ID", self.auth_provider.client_id())
-            .header(
-                header::AUTHORIZATION,
-                format!(
-                    "OAuth {}",
-                    access_token!(self.auth_provider, AuthenticatedRequestError)
-                ),
-            )
+        self.update_channel_with_meta(channel_id, update).await?;
+        Ok(())
+    }
+
+    /// Change only the title of the user's channel. See [`Client::update_channel`].
+    pub async fn set_title(
+        &self,
+        channel_id: impl Into<String>,
+        title: impl Into<String>,
+    ) -> Result<(), AuthenticatedRequestError<A::Error>> {
+        self.update_channel(channel_id, ChannelUpdate::new().with_live_title(title))
+            .await
+    }
+
+    /// Change only the category of the user's channel. See [`Client::update_channel`].
+    pub async fn set_category(
+        &self,
+        channel_id: impl Into<String>,
+        category_id: impl Into<String>,
+    ) -> Result<(), AuthenticatedRequestError<A::Error>> {
+        self.update_channel(
+            channel_id,
+            ChannelUpdate::new().with_category_id(category_id),
+        )
+        .await
+    }
+
+    /// Change only the language of the user's channel, as a 2 letter ISO code. See
+    /// [`Client::update_channel`].
+    pub async fn set_language(
+        &self,
+        channel_id: impl Into<String>,
+        language_code: impl Into<String>,
+    ) -> Result<(), AuthenticatedRequestError<A::Error>> {
+        self.update_channel(
+            channel_id,
+            ChannelUpdate::new().with_language_code(language_code),
+        )
+        .await
+    }
+
+    /// Like [`Client::update_channel`], but also returns the response's metadata.
+    pub async fn update_channel_with_meta(
+        &self,
+        channel_id: impl Into<String>,
+        update: ChannelUpdate,
+    ) -> Result<WithMeta<()>, AuthenticatedRequestError<A::Error>> {
+        self.require_scope(Scope::ChannelUpdateSelf)?;
+        let req = self
+            .request(Method::POST, "channels/update")
             .json(&ChannelUpdatePayload {
                 channel_id: channel_id.into(),
                 update,
-            })
-            .send()
-            .await?;
+            })?;
 
-        if ApiError::can_handle_code(res.status()) {
-            let err: ApiError = res.json().await.unwrap_or_default();
-            Err(AuthenticatedRequestError::ApiError(err))
-        } else {
-            res.error_for_status()?;
-            Ok(())
+        let res = self.send_authenticated(req).await?;
+        Ok(WithMeta {
+            data: (),
+            meta: ResponseMeta::from(&res),
+        })
+    }
+}
+
+/// Set the authorization header of the request to the given access token
+fn set_access_token(request: &mut HttpRequest, token: &str) -> Result<(), RequestError> {
+    let mut value = HeaderValue::from_str(&format!("OAuth {}", token))
+        .map_err(|err| RequestError::Transport(TransportError::new(err)))?;
+    value.set_sensitive(true);
+    request.headers_mut().insert(header::AUTHORIZATION, value);
+    Ok(())
+}
+
+/// Returns true if the api rejected the request's access token
+fn is_token_rejected(err: &RequestError) -> bool {
+    match err {
+        RequestError::ApiError(err) => {
+            err.is_invalid_token() || err.http_status == Some(StatusCode::UNAUTHORIZED)
+        }
+        RequestError::Status(status) => *status == StatusCode::UNAUTHORIZED,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ClientId;
+
+    #[test]
+    fn clones_share_transport() {
+        let client = ClientBuilder::new()
+            .rate_limits(crate::RateLimits::new())
+            .build(ClientId::new("client id"));
+        let clone = client.clone();
+        assert!(Arc::ptr_eq(&client.http, &clone.http));
+        assert!(Arc::ptr_eq(
+            client.rate_limiter.as_ref().unwrap(),
+            clone.rate_limiter.as_ref().unwrap()
+        ));
+
+        let other = client.as_user(ClientId::new("other"));
+        assert!(Arc::ptr_eq(&client.http, &other.http));
+    }
+
+    #[test]
+    fn user_agent_identifies_app() {
+        let client = ClientBuilder::new()
+            .user_agent("my-bot/1.0")
+            .build(ClientId::new("client id"));
+        let request = client.request(Method::GET, "test").empty().unwrap();
+        assert_eq!(
+            request.headers()[header::USER_AGENT],
+            concat!("trovo-rs/", env!("CARGO_PKG_VERSION"), " my-bot/1.0")
+        );
+    }
+
+    /// Responds to requests to `path` with the json body
+    #[derive(Debug)]
+    struct Json(&'static str, &'static str);
+
+    #[async_trait::async_trait]
+    impl HttpTransport for Json {
+        async fn execute(&self, request: HttpRequest) -> Result<HttpResponse, TransportError> {
+            assert_eq!(request.uri().path(), self.0);
+            Ok(http::Response::new(self.1.as_bytes().to_vec()))
+        }
+    }
+
+    fn json_client(path: &'static str, body: &'static str) -> Client<ClientId> {
+        Client::with_transport(Json(path, body), ClientId::new("client id"))
+    }
+
+    #[tokio::test]
+    async fn calls_parse_custom_types() {
+        #[derive(serde::Deserialize)]
+        struct Top {
+            category_info: Vec<serde_json::Value>,
         }
+
+        let client = json_client("/openplatform/categorys/top", r#"{"category_info": [{}]}"#);
+        let top: Top = client
+            .public_call(Method::GET, "/categorys/top", &())
+            .await
+            .unwrap();
+        assert_eq!(top.category_info.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn categories_are_parsed() {
+        let client = json_client(
+            "/openplatform/categorys/top",
+            r#"{"category_info": [
+                {"id": "10237", "name": "Minecraft", "short_name": "MC", "icon_url": "", "desc": ""}
+            ]}"#,
+        );
+        let categories = client.categories().await.unwrap();
+        assert_eq!(categories.len(), 1);
+        assert_eq!(categories[0].id, "10237");
+        assert_eq!(categories[0].short_name, "MC");
+    }
+
+    #[tokio::test]
+    async fn followers_are_paged() {
+        let client = json_client(
+            "/openplatform/channels/100/followers",
+            r#"{"total": "3", "total_page": 2, "follower": [
+                {"user_id": "1", "nickname": "one", "profile_pic": "", "followed_at": "1600000000"}
+            ]}"#,
+        );
+        let query = FollowersQuery {
+            limit: Some(2),
+            ..Default::default()
+        };
+        let page = client.channel_followers("100", &query).await.unwrap();
+        assert_eq!(page.total, 3);
+        assert_eq!(page.followers[0].followed_at.timestamp(), 1_600_000_000);
+
+        let next = page.next_page(&query).unwrap();
+        assert_eq!((next.limit, next.cursor), (Some(2), Some(1)));
+        assert!(page.next_page(&next).is_none());
+    }
+
+    #[tokio::test]
+    async fn viewers_are_grouped() {
+        let client = json_client(
+            "/openplatform/channels/100/viewers",
+            r#"{"live_title": "title", "nickname": "streamer", "total": "2", "total_page": 1,
+                "chatters": {
+                    "all": {"viewers": ["streamer", "fan"]},
+                    "creators": {"viewers": ["streamer"]},
+                    "VIPS": {"viewers": []},
+                    "Regulars": {"viewers": ["fan"]}
+                }}"#,
+        );
+        let query = ViewersQuery::default();
+        let viewers = client.channel_viewers("100", &query).await.unwrap();
+        assert_eq!(viewers.total, 2);
+        assert_eq!(viewers.chatters.creators.viewers, ["streamer"]);
+        assert_eq!(viewers.chatters.custom_roles["Regulars"].viewers, ["fan"]);
+        assert!(viewers.next_page(&query).is_none());
+    }
+
+    #[tokio::test]
+    async fn snapshots_only_fetch_what_they_need() {
+        use std::sync::Mutex;
+
+        /// Responds to channel lookups with the body, and fails every other request
+        #[derive(Debug)]
+        struct Channel(String, Mutex<Vec<String>>);
+
+        #[async_trait::async_trait]
+        impl HttpTransport for Channel {
+            async fn execute(&self, request: HttpRequest) -> Result<HttpResponse, TransportError> {
+                let path = request.uri().path().to_string();
+                let response = if path == "/openplatform/channels/id" {
+                    http::Response::new(self.0.as_bytes().to_vec())
+                } else if path.ends_with("/followers") {
+                    http::Response::new(br#"{"total": "7", "total_page": 7}"#.to_vec())
+                } else {
+                    http::Response::builder()
+                        .status(503)
+                        .body(Vec::new())
+                        .unwrap()
+                };
+                self.1.lock().unwrap().push(path);
+                Ok(response)
+            }
+        }
+
+        let channel = |username: &str| {
+            format!(
+                r#"{{"is_live": false, "category_id": "", "category_name": "", "live_title": "",
+                    "audi_type": "CHANNEL_AUDIENCE_TYPE_TEEN", "language_code": "",
+                    "thumbnail": "", "current_viewers": 0, "followers": 7, "streamer_info": "",
+                    "profile_pic": "", "channel_url": "", "created_at": "1600000000",
+                    "subscriber_num": 2, "username": "{}", "social_links": [],
+                    "started_at": "1600000000", "ended_at": "1600000000"}}"#,
+                username
+            )
+        };
+
+        // The nulled out channel trovo responds with when it isn't found
+        let transport = Arc::new(Channel(channel(""), Mutex::default()));
+        let client = Client::with_transport(transport.clone(), ClientId::new("client id"));
+        assert!(client.channel_snapshot("100").await.unwrap().is_none());
+        assert_eq!(*transport.1.lock().unwrap(), ["/openplatform/channels/id"]);
+
+        let transport = Arc::new(Channel(channel("streamer"), Mutex::default()));
+        let client = Client::with_transport(transport.clone(), ClientId::new("client id"));
+        let snapshot = client.channel_snapshot("100").await.unwrap().unwrap();
+        assert_eq!(
+            (snapshot.followers, snapshot.subscribers, snapshot.viewers),
+            (7, 2, 0)
+        );
+        assert_eq!(
+            *transport.1.lock().unwrap(),
+            [
+                "/openplatform/channels/id",
+                "/openplatform/channels/100/followers"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn unauthorized_responses_refresh_the_token() {
+        use std::sync::Mutex;
+
+        /// Rejects requests that aren't sent with the refreshed token
+        #[derive(Debug, Default)]
+        struct RequireRefreshed(Mutex<Vec<String>>);
+
+        #[async_trait::async_trait]
+        impl HttpTransport for RequireRefreshed {
+            async fn execute(&self, request: HttpRequest) -> Result<HttpResponse, TransportError> {
+                let token = request.headers()[header::AUTHORIZATION].to_str().unwrap();
+                self.0.lock().unwrap().push(token.to_string());
+                let response = if token.ends_with("refreshed") {
+                    http::Response::new(b"{}".to_vec())
+                } else {
+                    // No trovo error body, only the status code
+                    http::Response::builder()
+                        .status(401)
+                        .body(Vec::new())
+                        .unwrap()
+                };
+                Ok(response)
+            }
+        }
+
+        #[derive(Debug)]
+        struct Refreshable;
+
+        impl ClientIdProvider for Refreshable {
+            fn client_id(&self) -> &str {
+                "client id"
+            }
+        }
+
+        #[async_trait::async_trait]
+        impl AccessTokenProvider for Refreshable {
+            type Error = std::convert::Infallible;
+
+            fn access_token(&self) -> AccessToken {
+                AccessToken::Token("stale".to_string())
+            }
+
+            async fn refresh_token(&self) -> Result<String, Self::Error> {
+                Ok("refreshed".to_string())
+            }
+        }
+
+        let transport = Arc::new(RequireRefreshed::default());
+        let client = ClientBuilder::new()
+            .transport(transport.clone())
+            .build(Refreshable);
+        let request = client.request(Method::GET, "validate").empty().unwrap();
+        client.send_authenticated(request).await.unwrap();
+        assert_eq!(
+            *transport.0.lock().unwrap(),
+            ["OAuth stale", "OAuth refreshed"]
+        );
+    }
+
+    #[tokio::test]
+    async fn hooks_see_each_attempt() {
+        use std::sync::Mutex;
+
+        /// Fails the first request with a server error
+        #[derive(Debug, Default)]
+        struct FailOnce(Mutex<bool>);
+
+        #[async_trait::async_trait]
+        impl HttpTransport for FailOnce {
+            async fn execute(&self, _request: HttpRequest) -> Result<HttpResponse, TransportError> {
+                let failed = std::mem::replace(&mut *self.0.lock().unwrap(), true);
+                let status = if failed { 200 } else { 503 };
+                Ok(http::Response::builder()
+                    .status(status)
+                    .body(Vec::new())
+                    .unwrap())
+            }
+        }
+
+        #[derive(Debug, Default)]
+        struct Recorder(Mutex<Vec<String>>);
+
+        impl ClientHooks for Arc<Recorder> {
+            fn on_request_start(&self, request: &RequestInfo) {
+                self.0
+                    .lock()
+                    .unwrap()
+                    .push(format!("start {}", request.attempt));
+            }
+
+            fn on_request_end(&self, request: &RequestInfo, outcome: &RequestOutcome<'_>) {
+                self.0.lock().unwrap().push(format!(
+                    "end {} {}",
+                    request.attempt,
+                    outcome.status.unwrap()
+                ));
+            }
+
+            fn on_retry(&self, request: &RequestInfo, _error: &RequestError, _backoff: Duration) {
+                self.0
+                    .lock()
+                    .unwrap()
+                    .push(format!("retry {}", request.attempt));
+            }
+        }
+
+        let recorder = Arc::new(Recorder::default());
+        let client = ClientBuilder::new()
+            .transport(FailOnce::default())
+            .retry(RetryPolicy::new(2).with_initial_backoff(Duration::from_millis(1)))
+            .hooks(recorder.clone())
+            .build(ClientId::new("client id"));
+        let request = client.request(Method::GET, "test").empty().unwrap();
+        client.send(request).await.unwrap();
+        assert_eq!(
+            *recorder.0.lock().unwrap(),
+            [
+                "start 1",
+                "end 1 503 Service Unavailable",
+                "retry 1",
+                "start 2",
+                "end 2 200 OK"
+            ]
+        );
     }
 }
@@ -0,0 +1,247 @@
+use crate::{HttpRequest, HttpResponse, Layer, Next, TransportError};
+use http::{header, HeaderMap, HeaderValue, Method, StatusCode, Uri, Version};
+use std::{collections::HashMap, sync::Mutex};
+use tokio::sync::watch;
+
+/// Api endpoints that only read data despite using `POST`
+pub(crate) const READ_ONLY_POST_PATHS: [&str; 9] = [
+    "/getusers",
+    "/channels/id",
+    "/getemotes",
+    "/gettopchannels",
+    "/searchcategory",
+    "/followers",
+    "/viewers",
+    "/clips",
+    "/paststreams",
+];
+
+/// [`Layer`] that merges identical requests made at the same time into one, sharing the response
+/// between all of the callers.
+///
+/// `GET` requests and `POST` requests to endpoints that only read data, such as `getusers` and
+/// `channels/id`, are merged when they have the same url, body and access token. Add other
+/// endpoints with [`RequestCoalescer::with_path`]. Add this layer before any caching layer so
+/// only one request per resource reaches the cache.
+///
+/// ```no_run
+/// use trovo::{ClientBuilder, ClientId, RequestCoalescer};
+///
+/// let client = ClientBuilder::new()
+///     .with_layer(RequestCoalescer::new())
+///     .build(ClientId::new("client id"));
+/// ```
+#[derive(Debug)]
+pub struct RequestCoalescer {
+    paths: Vec<String>,
+    in_flight: Mutex<HashMap<RequestKey, watch::Receiver<Option<Shared>>>>,
+}
+
+/// Identifies requests that read the same data as the same user
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct RequestKey {
+    method: Method,
+    uri: Uri,
+    authorization: Option<HeaderValue>,
+    body: Vec<u8>,
+}
+
+impl RequestKey {
+    /// Key of the request if it is a `GET`, or a `POST` to a path ending with one of the given
+    /// read only paths
+    pub(crate) fn for_read(request: &HttpRequest, read_only_paths: &[String]) -> Option<Self> {
+        let path = request.uri().path();
+        let read = request.method() == Method::GET
+            || (request.method() == Method::POST
+                && read_only_paths
+                    .iter()
+                    .any(|suffix| path.ends_with(suffix.as_str())));
+        if !read {
+            return None;
+        }
+        Some(Self {
+            method: request.method().clone(),
+            uri: request.uri().clone(),
+            authorization: request.headers().get(header::AUTHORIZATION).cloned(),
+            body: request.body().clone(),
+        })
+    }
+}
+
+/// A response, or the error message, that can be handed to every caller
+type Shared = Result<SharedResponse, String>;
+
+#[derive(Debug, Clone)]
+struct SharedResponse {
+    status: StatusCode,
+    version: Version,
+    headers: HeaderMap,
+    body: Vec<u8>,
+}
+
+impl Default for RequestCoalescer {
+    fn default() -> Self {
+        Self {
+            paths: READ_ONLY_POST_PATHS
+                .iter()
+                .map(|path| path.to_string())
+                .collect(),
+            in_flight: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl RequestCoalescer {
+    /// Create a layer that merges `GET` requests and `POST` requests to the read only endpoints
+    /// this crate knows about
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Also merge `POST` requests to endpoints whose path ends with the given path, such as
+    /// `/channels/id`. Only add endpoints that don't change anything.
+    pub fn with_path(mut self, path: impl Into<String>) -> Self {
+        self.paths.push(path.into());
+        self
+    }
+}
+
+#[async_trait::async_trait]
+impl Layer for RequestCoalescer {
+    async fn handle(
+        &self,
+        request: HttpRequest,
+        next: Next<'_>,
+    ) -> Result<HttpResponse, TransportError> {
+        let key = match RequestKey::for_read(&request, &self.paths) {
+            Some(key) => key,
+            None => return next.run(request).await,
+        };
+
+        let existing = {
+            let mut in_flight = self.in_flight.lock().unwrap();
+            match in_flight.get(&key) {
+                Some(rx) => Err(rx.clone()),
+                None => {
+                    let (tx, rx) = watch::channel(None);
+                    in_flight.insert(key.clone(), rx);
+                    Ok(tx)
+                }
+            }
+        };
+
+        match existing {
+            Ok(tx) => {
+                // Removes the entry even if this request is cancelled, so later requests don't
+                // wait on a response that will never come
+                let _guard = RemoveOnDrop {
+                    in_flight: &self.in_flight,
+                    key,
+                };
+                let res = next.run(request).await;
+                let shared = match &res {
+                    Ok(res) => Ok(SharedResponse {
+                        status: res.status(),
+                        version: res.version(),
+                        headers: res.headers().clone(),
+                        body: res.body().clone(),
+                    }),
+                    Err(err) => Err(err.to_string()),
+                };
+                let _ = tx.send(Some(shared));
+                res
+            }
+            Err(mut rx) => {
+                trace!(uri = %request.uri(), "waiting for identical in-flight request");
+                loop {
+                    if let Some(shared) = &*rx.borrow() {
+                        return shared
+                            .clone()
+                            .map(into_response)
+                            .map_err(TransportError::new);
+                    }
+                    if rx.changed().await.is_err() {
+                        // The original request was cancelled, send this one instead
+                        return next.run(request).await;
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn into_response(shared: SharedResponse) -> HttpResponse {
+    let mut res = http::Response::new(shared.body);
+    *res.status_mut() = shared.status;
+    *res.version_mut() = shared.version;
+    *res.headers_mut() = shared.headers;
+    res
+}
+
+struct RemoveOnDrop<'a> {
+    in_flight: &'a Mutex<HashMap<RequestKey, watch::Receiver<Option<Shared>>>>,
+    key: RequestKey,
+}
+
+impl Drop for RemoveOnDrop<'_> {
+    fn drop(&mut self) {
+        if let Ok(mut in_flight) = self.in_flight.lock() {
+            in_flight.remove(&self.key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{layer::Layered, HttpTransport};
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+
+    #[derive(Debug, Default)]
+    struct Counting(AtomicUsize);
+
+    #[async_trait::async_trait]
+    impl HttpTransport for Counting {
+        async fn execute(&self, _request: HttpRequest) -> Result<HttpResponse, TransportError> {
+            let count = self.0.fetch_add(1, Ordering::SeqCst) + 1;
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            Ok(http::Response::new(count.to_string().into_bytes()))
+        }
+    }
+
+    fn request(method: Method, path: &str) -> HttpRequest {
+        let mut request = http::Request::new(Vec::new());
+        *request.method_mut() = method;
+        *request.uri_mut() = format!("https://example.com{}", path).parse().unwrap();
+        request
+    }
+
+    #[tokio::test]
+    async fn identical_requests_are_merged() {
+        let counting = Arc::new(Counting::default());
+        let transport = Layered {
+            layers: vec![Arc::new(RequestCoalescer::new())],
+            transport: counting.clone(),
+        };
+
+        let (a, b) = tokio::join!(
+            transport.execute(request(Method::POST, "/openplatform/channels/id")),
+            transport.execute(request(Method::POST, "/openplatform/channels/id")),
+        );
+        assert_eq!(a.unwrap().body(), b"1");
+        assert_eq!(b.unwrap().body(), b"1");
+
+        let (a, b) = tokio::join!(
+            transport.execute(request(Method::POST, "/openplatform/chat/send")),
+            transport.execute(request(Method::POST, "/openplatform/chat/send")),
+        );
+        assert_ne!(a.unwrap().body(), b.unwrap().body());
+        assert_eq!(counting.0.load(Ordering::SeqCst), 3);
+    }
+}
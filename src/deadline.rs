@@ -0,0 +1,74 @@
+use crate::{time, TransportError};
+use futures::future::BoxFuture;
+use std::{future::Future, time::Duration};
+
+/// Bound how long an api call can take in total, including retries, rate limiting and token
+/// refreshes, without losing its error type.
+///
+/// If the deadline passes the call is cancelled and fails with a transport error whose
+/// [`is_timeout`](TransportError::is_timeout) returns true. Unlike
+/// [`Client::with_timeout`](crate::Client::with_timeout), which applies to each attempt
+/// separately, the deadline covers the whole call.
+///
+/// Cancelling a call leaves the client usable, but isn't safe for every call:
+///
+/// - Calls that refresh the token of a
+///   [`RefreshingTokenProvider`](crate::RefreshingTokenProvider), directly or through an
+///   [`EnvAuthProvider`](crate::EnvAuthProvider), can lose the new refresh token if cancelled
+///   while the refresh request is in flight, as Trovo only accepts each refresh token once. The
+///   same goes for [`CodeExchange::refresh`](crate::CodeExchange::refresh).
+/// - Calls that change something, such as sending chat messages, chat commands and deleting
+///   messages, may still have been handled by Trovo when cancelled.
+///
+/// Other calls can be cancelled at any point.
+///
+/// ```no_run
+/// # async fn run(client: trovo::Client<trovo::ClientId>) {
+/// use std::time::Duration;
+/// use trovo::DeadlineExt;
+///
+/// match client.user("username").with_deadline(Duration::from_secs(5)).await {
+///     Ok(user) => println!("found {:?}", user),
+///     Err(trovo::RequestError::Transport(err)) if err.is_timeout() => println!("too slow"),
+///     Err(err) => println!("failed: {}", err),
+/// }
+/// # }
+/// ```
+pub trait DeadlineExt<T, E>: Future<Output = Result<T, E>> + Sized {
+    /// Fail with a timeout error if the call hasn't finished within `deadline`
+    fn with_deadline<'a>(self, deadline: Duration) -> BoxFuture<'a, Result<T, E>>
+    where
+        Self: Send + 'a,
+        E: From<TransportError>,
+    {
+        Box::pin(async move {
+            match time::timeout(deadline, self).await {
+                Ok(res) => res,
+                Err(elapsed) => Err(TransportError::new(elapsed).into()),
+            }
+        })
+    }
+}
+
+impl<F, T, E> DeadlineExt<T, E> for F where F: Future<Output = Result<T, E>> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RequestError;
+
+    #[tokio::test]
+    async fn deadline_times_out() {
+        let slow = async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            Ok::<_, RequestError>(())
+        };
+        match slow.with_deadline(Duration::from_millis(10)).await {
+            Err(RequestError::Transport(err)) => assert!(err.is_timeout()),
+            res => panic!("unexpected result {:?}", res),
+        }
+
+        let fast = async { Ok::<_, RequestError>(1) };
+        assert_eq!(fast.with_deadline(Duration::from_secs(1)).await.unwrap(), 1);
+    }
+}
@@ -1,10 +1,16 @@
+use crate::Secret;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_repr::*;
-use serde_with::rust::string_empty_as_none;
+use serde_with::{rust::string_empty_as_none, serde_as, DisplayFromStr, PickFirst};
+use std::{
+    collections::HashMap,
+    convert::TryFrom,
+    fmt::{self, Display},
+};
 
 /// User details returned by [`Client::users`](crate::Client::users)
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct User {
     /// Unique id of a user.
     pub user_id: String,
@@ -40,6 +46,13 @@ pub struct GetChannelByIdPayload {
     pub channel_id: String,
 }
 
+/// Payload for the get channel info api, by username
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetChannelByUsernamePayload {
+    /// Username of the channel you are requesting. Not case sensitive.
+    pub username: String,
+}
+
 /// Response for the get users api
 #[derive(Debug, Deserialize)]
 pub struct ChannelInfo {
@@ -102,8 +115,76 @@ pub struct ChannelInfo {
     pub ended_at: DateTime<Utc>,
 }
 
-/// Audience type of a channel
-#[derive(Debug, Serialize, Deserialize)]
+/// Whether a channel is live and what it's streaming, returned by
+/// [`Client::is_live`](crate::Client::is_live)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LiveStatus {
+    /// If the channel is currently live streaming.
+    pub is_live: bool,
+
+    /// Current title of the channel.
+    pub live_title: String,
+
+    /// Name of the channel's category.
+    pub category_name: String,
+
+    /// Number of current viewers.
+    pub current_viewers: u64,
+
+    /// The latest streaming start time of the channel.
+    pub started_at: DateTime<Utc>,
+}
+
+impl From<ChannelInfo> for LiveStatus {
+    fn from(channel: ChannelInfo) -> Self {
+        Self {
+            is_live: channel.is_live,
+            live_title: channel.live_title,
+            category_name: channel.category_name,
+            current_viewers: channel.current_viewers,
+            started_at: channel.started_at,
+        }
+    }
+}
+
+/// A channel's info and audience counts at one point in time, returned by
+/// [`Client::channel_snapshot`](crate::Client::channel_snapshot)
+#[derive(Debug)]
+pub struct ChannelSnapshot {
+    /// The channel's info.
+    pub info: ChannelInfo,
+
+    /// Number of followers, from the followers list.
+    pub followers: u64,
+
+    /// Number of subscribers.
+    pub subscribers: u64,
+
+    /// Number of viewers in chat, from the viewers list. Zero if the channel isn't live.
+    pub viewers: u64,
+}
+
+/// Details of the authenticated user's own channel, returned by
+/// [`Client::channel_for_user`](crate::Client::channel_for_user)
+#[derive(Debug, Deserialize)]
+pub struct UserChannelInfo {
+    /// Unique id of the user.
+    pub uid: String,
+
+    /// Unique id of the channel.
+    pub channel_id: String,
+
+    /// The channel's stream key, used with Trovo's RTMP ingest server to go live.
+    pub stream_key: Secret,
+
+    /// The channel's public information.
+    #[serde(flatten)]
+    pub info: ChannelInfo,
+}
+
+/// Audience type of a channel, read from [`ChannelInfo::audi_type`] and set with
+/// [`ChannelUpdate::with_audi_type`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum AudienceType {
     /// Family friendly
     #[serde(rename = "CHANNEL_AUDIENCE_TYPE_FAMILYFRIENDLY")]
@@ -118,6 +199,27 @@ pub enum AudienceType {
     EighteenPlus,
 }
 
+impl AudienceType {
+    /// Name of the audience type as used by the Trovo api
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::FamilyFriendly => "CHANNEL_AUDIENCE_TYPE_FAMILYFRIENDLY",
+            Self::Teen => "CHANNEL_AUDIENCE_TYPE_TEEN",
+            Self::EighteenPlus => "CHANNEL_AUDIENCE_TYPE_EIGHTEENPLUS",
+        }
+    }
+}
+
+impl Display for AudienceType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::FamilyFriendly => "Family friendly",
+            Self::Teen => "Teen",
+            Self::EighteenPlus => "18+",
+        })
+    }
+}
+
 /// Social media link for a channel
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SocialLink {
@@ -130,10 +232,11 @@ pub struct SocialLink {
 }
 
 /// Types of emotes to fetch
-#[derive(Serialize_repr, Deserialize_repr, PartialEq, Debug)]
+#[derive(Serialize_repr, Deserialize_repr, PartialEq, Debug, Default)]
 #[repr(i16)]
 pub enum EmoteFetchType {
     /// Get platform-level emoticons and custom emoticons corresponding to channel IDs
+    #[default]
     All = 0,
 
     /// Get the custom emoji corresponding to the channel IDs
@@ -143,12 +246,6 @@ pub enum EmoteFetchType {
     Platform = 2,
 }
 
-impl Default for EmoteFetchType {
-    fn default() -> Self {
-        Self::All
-    }
-}
-
 /// Payload for the get emotes api call
 #[derive(Debug, Serialize, Deserialize)]
 pub struct GetEmotesPayload {
@@ -160,7 +257,7 @@ pub struct GetEmotesPayload {
 }
 
 /// Common emote payload across types
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Emote {
     /// Name of emote.
     pub name: String,
@@ -189,6 +286,14 @@ pub struct Emote {
     pub update_time: Option<String>,
 }
 
+impl Emote {
+    /// Text that shows this emote in chat messages, its name prefixed with a colon such as
+    /// `:happy`
+    pub fn code(&self) -> String {
+        format!(":{}", self.name)
+    }
+}
+
 /// Response for the get emotes api
 #[derive(Debug, Deserialize)]
 pub struct GetEmotesResponse {
@@ -197,7 +302,7 @@ pub struct GetEmotesResponse {
 }
 
 /// Map of different emote types
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct EmoteChannels {
     /// Container for customized/channel emotes
@@ -210,15 +315,81 @@ pub struct EmoteChannels {
     pub global_emotes: Vec<Emote>,
 }
 
+/// Where an emote in [`EmoteChannels`] comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EmoteKind {
+    /// Custom emotes of a channel
+    Channel,
+
+    /// Emotes of a platform event
+    Event,
+
+    /// Emotes everyone can use
+    Global,
+}
+
+impl EmoteChannels {
+    /// Iterate over every emote, channel emotes first, then event and global emotes
+    pub fn iter(&self) -> impl Iterator<Item = &Emote> {
+        self.customized_emotes
+            .channel
+            .iter()
+            .flat_map(|channel| &channel.emotes)
+            .chain(&self.event_emotes)
+            .chain(&self.global_emotes)
+    }
+
+    /// Iterate over the emotes of one kind, such as only the global emotes
+    pub fn of_kind(&self, kind: EmoteKind) -> Box<dyn Iterator<Item = &Emote> + '_> {
+        match kind {
+            EmoteKind::Channel => Box::new(
+                self.customized_emotes
+                    .channel
+                    .iter()
+                    .flat_map(|channel| &channel.emotes),
+            ),
+            EmoteKind::Event => Box::new(self.event_emotes.iter()),
+            EmoteKind::Global => Box::new(self.global_emotes.iter()),
+        }
+    }
+
+    /// Find the emote that the given chat text, such as `:happy`, shows
+    pub fn find_by_code(&self, code: &str) -> Option<&Emote> {
+        let name = code.strip_prefix(':')?;
+        self.iter().find(|emote| emote.name == name)
+    }
+
+    /// The custom emotes of the given channel, or None if they weren't requested
+    pub fn channel(&self, channel_id: &str) -> Option<&[Emote]> {
+        self.customized_emotes
+            .channel
+            .iter()
+            .find(|channel| channel.channel_id == channel_id)
+            .map(|channel| channel.emotes.as_slice())
+    }
+
+    /// Map from each emote's chat code, such as `:happy`, to its image url, for replacing codes
+    /// in chat messages.
+    ///
+    /// When emotes share a name, channel emotes take priority over event and global emotes.
+    pub fn urls_by_code(&self) -> HashMap<String, &str> {
+        let mut urls = HashMap::new();
+        for emote in self.iter() {
+            urls.entry(emote.code()).or_insert(emote.url.as_str());
+        }
+        urls
+    }
+}
+
 /// Container for customized emotes
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct CustomizedEmotes {
     /// List of channels and their emotes
     pub channel: Vec<ChannelEmotes>,
 }
 
 /// Channel id and its emotes
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct ChannelEmotes {
     /// Id of the channel
     pub channel_id: String,
@@ -238,18 +409,878 @@ pub struct ChannelUpdatePayload {
     pub update: ChannelUpdate,
 }
 
-/// Fields to update on a channel
+/// Fields to update on a channel. Fields left as None aren't changed.
+///
+/// ```
+/// use trovo::{AudienceType, ChannelUpdate};
+///
+/// let update = ChannelUpdate::new()
+///     .with_live_title("Speedrunning")
+///     .with_audi_type(AudienceType::Teen);
+/// ```
 #[derive(Debug, Default, Serialize)]
 pub struct ChannelUpdate {
     /// Name of user’s channel
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub live_title: Option<String>,
 
     /// Represent which game is the user playing in their channel.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub category_id: Option<String>,
 
     /// 2 character language ISO 2 code, see standard: https://www.sitepoint.com/iso-2-letter-language-codes/
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub language_code: Option<String>,
 
     /// 3 options representing age range
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub audi_type: Option<AudienceType>,
 }
+
+impl ChannelUpdate {
+    /// Create an update that doesn't change anything yet
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Change the channel's title
+    pub fn with_live_title(mut self, live_title: impl Into<String>) -> Self {
+        self.live_title = Some(live_title.into());
+        self
+    }
+
+    /// Change the channel's category, such as to an id from
+    /// [`Client::search_categories`](crate::Client::search_categories)
+    pub fn with_category_id(mut self, category_id: impl Into<String>) -> Self {
+        self.category_id = Some(category_id.into());
+        self
+    }
+
+    /// Change the channel's language, as a 2 letter ISO code
+    pub fn with_language_code(mut self, language_code: impl Into<String>) -> Self {
+        self.language_code = Some(language_code.into());
+        self
+    }
+
+    /// Change the channel's audience type
+    pub fn with_audi_type(mut self, audi_type: AudienceType) -> Self {
+        self.audi_type = Some(audi_type);
+        self
+    }
+}
+
+/// Payload for the get top channels api, used with [`Client::top_channels`](crate::Client::top_channels)
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct TopChannelsQuery {
+    /// Maximum number of channels per page, up to 100. Defaults to 20.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+
+    /// Only list channels streaming in this category.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category_id: Option<String>,
+
+    /// Paging token returned with a previous page.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+
+    /// Index of the page to get, starting from 0.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<u32>,
+
+    /// Whether to page forwards from the token rather than backwards.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub after: Option<bool>,
+}
+
+/// A page of live channels returned by [`Client::top_channels`](crate::Client::top_channels)
+#[derive(Debug, Deserialize)]
+pub struct TopChannels {
+    /// Channels on this page, with the most viewers first.
+    #[serde(rename = "top_channels_lists", default)]
+    pub channels: Vec<TopChannel>,
+
+    /// Total number of pages.
+    #[serde(default)]
+    pub total_page: u32,
+
+    /// Token to get the other pages with.
+    #[serde(default)]
+    pub token: String,
+}
+
+impl TopChannels {
+    /// Query for the page after this one, or None if this is the last page. `query` must be the
+    /// query this page was requested with.
+    pub fn next_page(&self, query: &TopChannelsQuery) -> Option<TopChannelsQuery> {
+        let cursor = query.cursor.unwrap_or(0).checked_add(1)?;
+        if cursor >= self.total_page || self.channels.is_empty() {
+            return None;
+        }
+        Some(TopChannelsQuery {
+            token: Some(self.token.clone()),
+            cursor: Some(cursor),
+            after: Some(true),
+            ..query.clone()
+        })
+    }
+}
+
+/// A live channel listed by [`Client::top_channels`](crate::Client::top_channels)
+#[derive(Debug, Deserialize)]
+pub struct TopChannel {
+    /// Unique id of the channel.
+    pub channel_id: String,
+
+    /// If the channel is currently live streaming.
+    #[serde(default)]
+    pub is_live: bool,
+
+    /// The id of the game category.
+    #[serde(default)]
+    pub category_id: String,
+
+    /// Text name of the category.
+    #[serde(default)]
+    pub category_name: String,
+
+    /// Current title of the channel.
+    #[serde(default)]
+    pub live_title: String,
+
+    /// Audience type of the channel, if known.
+    #[serde(default)]
+    pub audi_type: Option<AudienceType>,
+
+    /// Language of the channel in ISO 2 (2 letter language code). The api can't filter by
+    /// language, so filter on this instead.
+    #[serde(default)]
+    pub language_code: String,
+
+    /// Url of the thumbnail for the current stream.
+    #[serde(default)]
+    pub thumbnail: String,
+
+    /// Number of current viewers.
+    #[serde(default)]
+    pub current_viewers: u64,
+
+    /// Number of followers.
+    #[serde(default)]
+    pub num_followers: u64,
+
+    /// Profile information of the streamer.
+    #[serde(default)]
+    pub streamer_info: String,
+
+    /// Url of the streamer's profile picture.
+    #[serde(default)]
+    pub profile_pic: String,
+
+    /// Username of the streamer, the last part of the channel url.
+    #[serde(default)]
+    pub username: String,
+
+    /// Display name of the streamer.
+    #[serde(default)]
+    pub nick_name: String,
+}
+
+/// Payload for the search category api
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SearchCategoryPayload {
+    /// Text to search categories for.
+    pub query: String,
+
+    /// Maximum number of categories to return, up to 100.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+}
+
+/// Response for the category apis
+#[derive(Debug, Deserialize)]
+pub struct CategoriesResponse {
+    /// The categories found.
+    #[serde(default)]
+    pub category_info: Vec<Category>,
+}
+
+/// A game or other category that channels stream in
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Category {
+    /// Unique id of the category, used for [`ChannelUpdate::category_id`].
+    pub id: String,
+
+    /// Name of the category.
+    pub name: String,
+
+    /// Short name of the category.
+    #[serde(default)]
+    pub short_name: String,
+
+    /// Url of the category's icon.
+    #[serde(default)]
+    pub icon_url: String,
+
+    /// Description of the category.
+    #[serde(default)]
+    pub desc: String,
+}
+
+/// Order to list items in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortDirection {
+    /// Oldest first
+    Asc,
+
+    /// Newest first
+    Desc,
+}
+
+/// Payload for the get channel followers api, used with
+/// [`Client::channel_followers`](crate::Client::channel_followers)
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct FollowersQuery {
+    /// Maximum number of followers per page, up to 100. Defaults to 20.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+
+    /// Index of the page to get, starting from 0.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<u32>,
+
+    /// Order of the followers by when they followed. Defaults to oldest first.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub direction: Option<SortDirection>,
+}
+
+/// A page of a channel's followers returned by
+/// [`Client::channel_followers`](crate::Client::channel_followers)
+#[serde_as]
+#[derive(Debug, Deserialize)]
+pub struct Followers {
+    /// Total number of followers of the channel.
+    #[serde_as(as = "PickFirst<(_, DisplayFromStr)>")]
+    #[serde(default)]
+    pub total: u64,
+
+    /// Followers on this page.
+    #[serde(rename = "follower", default)]
+    pub followers: Vec<Follower>,
+
+    /// Total number of pages.
+    #[serde(default)]
+    pub total_page: u32,
+}
+
+impl Followers {
+    /// Query for the page after this one, or None if this is the last page. `query` must be the
+    /// query this page was requested with.
+    pub fn next_page(&self, query: &FollowersQuery) -> Option<FollowersQuery> {
+        let cursor = query.cursor.unwrap_or(0).checked_add(1)?;
+        if cursor >= self.total_page || self.followers.is_empty() {
+            return None;
+        }
+        Some(FollowersQuery {
+            cursor: Some(cursor),
+            ..query.clone()
+        })
+    }
+}
+
+/// A user following a channel
+#[derive(Debug, Clone, Deserialize)]
+pub struct Follower {
+    /// Unique id of the user.
+    pub user_id: String,
+
+    /// Display name of the user.
+    #[serde(default)]
+    pub nickname: String,
+
+    /// Url of the user's profile picture.
+    #[serde(default)]
+    pub profile_pic: String,
+
+    /// When the user followed the channel.
+    #[serde(with = "serde_with::chrono::datetime_utc_ts_seconds_from_any")]
+    pub followed_at: DateTime<Utc>,
+}
+
+/// Paging for the get channel subscribers api, used with
+/// [`Client::channel_subscribers`](crate::Client::channel_subscribers)
+#[derive(Debug, Default, Clone)]
+pub struct SubscribersQuery {
+    /// Maximum number of subscribers per page, up to 100. Defaults to 25.
+    pub limit: Option<u32>,
+
+    /// Number of subscribers to skip.
+    pub offset: Option<u32>,
+
+    /// Order of the subscribers by when they subscribed. Defaults to oldest first.
+    pub direction: Option<SortDirection>,
+}
+
+impl SubscribersQuery {
+    /// Query string parameters for the query
+    pub(crate) fn to_pairs(&self) -> Vec<(&'static str, String)> {
+        let mut pairs = Vec::new();
+        if let Some(limit) = self.limit {
+            pairs.push(("limit", limit.to_string()));
+        }
+        if let Some(offset) = self.offset {
+            pairs.push(("offset", offset.to_string()));
+        }
+        if let Some(direction) = self.direction {
+            let direction = match direction {
+                SortDirection::Asc => "asc",
+                SortDirection::Desc => "desc",
+            };
+            pairs.push(("direction", direction.to_string()));
+        }
+        pairs
+    }
+}
+
+/// A page of a channel's subscribers returned by
+/// [`Client::channel_subscribers`](crate::Client::channel_subscribers)
+#[serde_as]
+#[derive(Debug, Deserialize)]
+pub struct Subscribers {
+    /// Total number of subscribers of the channel.
+    #[serde_as(as = "PickFirst<(_, DisplayFromStr)>")]
+    #[serde(default)]
+    pub total: u64,
+
+    /// Subscribers on this page.
+    #[serde(rename = "subscriptions", default)]
+    pub subscribers: Vec<Subscriber>,
+}
+
+impl Subscribers {
+    /// Query for the page after this one, or None if this is the last page. `query` must be the
+    /// query this page was requested with.
+    pub fn next_page(&self, query: &SubscribersQuery) -> Option<SubscribersQuery> {
+        let offset = u32::try_from(self.subscribers.len())
+            .ok()
+            .and_then(|len| query.offset.unwrap_or(0).checked_add(len))?;
+        if u64::from(offset) >= self.total || self.subscribers.is_empty() {
+            return None;
+        }
+        Some(SubscribersQuery {
+            offset: Some(offset),
+            ..query.clone()
+        })
+    }
+}
+
+/// A user subscribed to a channel
+#[derive(Debug, Clone, Deserialize)]
+pub struct Subscriber {
+    /// The subscribed user.
+    pub user: SubscriberUser,
+
+    /// When the user subscribed.
+    #[serde(with = "serde_with::chrono::datetime_utc_ts_seconds_from_any")]
+    pub sub_created_at: DateTime<Utc>,
+
+    /// Level of the subscription, such as `1`.
+    #[serde(default)]
+    pub sub_lv: String,
+
+    /// Tier of the subscription, such as `1`.
+    #[serde(default)]
+    pub sub_tier: String,
+}
+
+/// User details of a [`Subscriber`]
+#[derive(Debug, Clone, Deserialize)]
+pub struct SubscriberUser {
+    /// Unique id of the user.
+    pub user_id: String,
+
+    /// Username of the user.
+    #[serde(default)]
+    pub username: String,
+
+    /// Display name of the user.
+    #[serde(default)]
+    pub display_name: String,
+
+    /// Url of the user's profile picture.
+    #[serde(default)]
+    pub profile_pic: String,
+
+    /// When the user's account was created.
+    #[serde(with = "serde_with::chrono::datetime_utc_ts_seconds_from_any")]
+    pub created_at: DateTime<Utc>,
+}
+
+/// Payload for the get channel viewers api, used with
+/// [`Client::channel_viewers`](crate::Client::channel_viewers)
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct ViewersQuery {
+    /// Maximum number of viewers per page, up to 100000. Defaults to 20.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+
+    /// Index of the page to get, starting from 0.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<u32>,
+}
+
+/// A page of the viewers in a channel returned by
+/// [`Client::channel_viewers`](crate::Client::channel_viewers)
+#[serde_as]
+#[derive(Debug, Deserialize)]
+pub struct Viewers {
+    /// Current title of the channel.
+    #[serde(default)]
+    pub live_title: String,
+
+    /// Display name of the streamer.
+    #[serde(default)]
+    pub nickname: String,
+
+    /// Total number of viewers.
+    #[serde_as(as = "PickFirst<(_, DisplayFromStr)>")]
+    #[serde(default)]
+    pub total: u64,
+
+    /// Viewers on this page, by their roles in the channel.
+    #[serde(default)]
+    pub chatters: Chatters,
+
+    /// Total number of pages.
+    #[serde(default)]
+    pub total_page: u32,
+}
+
+impl Viewers {
+    /// Query for the page after this one, or None if this is the last page. `query` must be the
+    /// query this page was requested with.
+    pub fn next_page(&self, query: &ViewersQuery) -> Option<ViewersQuery> {
+        let cursor = query.cursor.unwrap_or(0).checked_add(1)?;
+        if cursor >= self.total_page || self.chatters.all.viewers.is_empty() {
+            return None;
+        }
+        Some(ViewersQuery {
+            cursor: Some(cursor),
+            ..query.clone()
+        })
+    }
+}
+
+/// Viewers of a channel grouped by role. A viewer with several roles is in each of their
+/// groups.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default)]
+pub struct Chatters {
+    /// Every viewer.
+    pub all: ViewerGroup,
+
+    /// The streamer.
+    pub creators: ViewerGroup,
+
+    /// Moderators of the channel.
+    pub moderators: ViewerGroup,
+
+    /// Editors of the channel.
+    pub editors: ViewerGroup,
+
+    /// Subscribers of the channel.
+    pub subscribers: ViewerGroup,
+
+    /// Followers of the channel.
+    pub followers: ViewerGroup,
+
+    /// Viewers with the Trovo VIP role.
+    #[serde(rename = "VIPS")]
+    pub vips: ViewerGroup,
+
+    /// Viewers with the Ace role.
+    pub ace: ViewerGroup,
+
+    /// Viewers with the Ace+ role.
+    pub aceplus: ViewerGroup,
+
+    /// Trovo super moderators.
+    pub supermods: ViewerGroup,
+
+    /// Trovo wardens.
+    pub wardens: ViewerGroup,
+
+    /// Viewers with the channel's custom roles, by role name.
+    #[serde(flatten)]
+    pub custom_roles: HashMap<String, ViewerGroup>,
+}
+
+/// The viewers in one role
+#[derive(Debug, Default, Clone, Deserialize)]
+pub struct ViewerGroup {
+    /// Display names of the viewers.
+    #[serde(default)]
+    pub viewers: Vec<String>,
+}
+
+/// Response for the get live stream urls api
+#[derive(Debug, Deserialize)]
+pub struct StreamUrlsResponse {
+    /// Playback urls for each quality.
+    #[serde(default)]
+    pub stream_urls: Vec<StreamUrl>,
+}
+
+/// Url to play a live stream at one quality
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamUrl {
+    /// Url of the m3u8 playlist.
+    pub play_url: String,
+
+    /// Description of the quality, such as `1080p`.
+    #[serde(default)]
+    pub desc: String,
+}
+
+/// Time period to list clips or past streams from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Period {
+    /// The last day
+    Day,
+
+    /// The last week
+    Week,
+
+    /// The last month
+    Month,
+
+    /// All time
+    All,
+}
+
+/// Filters and paging for the get clips api, used with [`Client::clips`](crate::Client::clips)
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct ClipsQuery {
+    /// Only list clips in this category.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category_id: Option<String>,
+
+    /// Only list clips made in this period. Defaults to a week.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub period: Option<Period>,
+
+    /// Only get this clip.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub clip_id: Option<String>,
+
+    /// Maximum number of clips per page, up to 100. Defaults to 20.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+
+    /// Index of the page to get, starting from 0.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<u32>,
+
+    /// Order of the clips by when they were made. Defaults to newest first.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub direction: Option<SortDirection>,
+}
+
+/// Payload for the get clips api
+#[derive(Debug, Serialize)]
+pub struct ClipsPayload {
+    /// Id of the channel
+    pub channel_id: String,
+
+    /// Filters and paging
+    #[serde(flatten)]
+    pub query: ClipsQuery,
+}
+
+/// A page of a channel's clips returned by [`Client::clips`](crate::Client::clips)
+#[derive(Debug, Deserialize)]
+pub struct Clips {
+    /// Clips on this page.
+    #[serde(rename = "clips_info", default)]
+    pub clips: Vec<Clip>,
+
+    /// Total number of pages.
+    #[serde(default)]
+    pub total_page: u32,
+}
+
+impl Clips {
+    /// Query for the page after this one, or None if this is the last page. `query` must be the
+    /// query this page was requested with.
+    pub fn next_page(&self, query: &ClipsQuery) -> Option<ClipsQuery> {
+        let cursor = query.cursor.unwrap_or(0).checked_add(1)?;
+        if cursor >= self.total_page || self.clips.is_empty() {
+            return None;
+        }
+        Some(ClipsQuery {
+            cursor: Some(cursor),
+            ..query.clone()
+        })
+    }
+}
+
+/// A clip of a stream
+#[derive(Debug, Clone, Deserialize)]
+pub struct Clip {
+    /// Unique id of the clip.
+    pub clip_id: String,
+
+    /// Title of the clip.
+    #[serde(default)]
+    pub title: String,
+
+    /// Url to watch the clip.
+    #[serde(default)]
+    pub url: String,
+
+    /// Url of the clip's thumbnail.
+    #[serde(default)]
+    pub thumbnail: String,
+
+    /// Length of the clip in seconds.
+    #[serde(default)]
+    pub duration: u64,
+
+    /// Number of views.
+    #[serde(default)]
+    pub views: u64,
+
+    /// Number of likes.
+    #[serde(default)]
+    pub likes: u64,
+
+    /// Language of the stream the clip is from, in ISO 2 (2 letter language code).
+    #[serde(default)]
+    pub language: String,
+
+    /// Id of the category the stream was in.
+    #[serde(default)]
+    pub category_id: String,
+
+    /// Name of the category the stream was in.
+    #[serde(default)]
+    pub category_name: String,
+
+    /// User id of the streamer.
+    #[serde(default)]
+    pub streamer_id: String,
+
+    /// Username of the streamer.
+    #[serde(default)]
+    pub streamer_username: String,
+
+    /// Username of the user that made the clip.
+    #[serde(default)]
+    pub made_by: String,
+
+    /// When the clip was made.
+    #[serde(with = "serde_with::chrono::datetime_utc_ts_seconds_from_any")]
+    pub created_at: DateTime<Utc>,
+}
+
+/// Filters and paging for the get past streams api, used with
+/// [`Client::past_streams`](crate::Client::past_streams)
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct PastStreamsQuery {
+    /// Only list streams in this category.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category_id: Option<String>,
+
+    /// Only list streams from this period. Defaults to a week.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub period: Option<Period>,
+
+    /// Only get this past stream.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub past_stream_id: Option<String>,
+
+    /// Maximum number of streams per page, up to 100. Defaults to 20.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+
+    /// Index of the page to get, starting from 0.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<u32>,
+
+    /// Order of the streams by when they started. Defaults to newest first.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub direction: Option<SortDirection>,
+}
+
+/// Payload for the get past streams api
+#[derive(Debug, Serialize)]
+pub struct PastStreamsPayload {
+    /// Id of the channel
+    pub channel_id: String,
+
+    /// Filters and paging
+    #[serde(flatten)]
+    pub query: PastStreamsQuery,
+}
+
+/// A page of a channel's past streams returned by
+/// [`Client::past_streams`](crate::Client::past_streams)
+#[derive(Debug, Deserialize)]
+pub struct PastStreams {
+    /// Past streams on this page.
+    #[serde(rename = "past_streams_info", default)]
+    pub past_streams: Vec<PastStream>,
+
+    /// Total number of pages.
+    #[serde(default)]
+    pub total_page: u32,
+}
+
+impl PastStreams {
+    /// Query for the page after this one, or None if this is the last page. `query` must be the
+    /// query this page was requested with.
+    pub fn next_page(&self, query: &PastStreamsQuery) -> Option<PastStreamsQuery> {
+        let cursor = query.cursor.unwrap_or(0).checked_add(1)?;
+        if cursor >= self.total_page || self.past_streams.is_empty() {
+            return None;
+        }
+        Some(PastStreamsQuery {
+            cursor: Some(cursor),
+            ..query.clone()
+        })
+    }
+}
+
+/// A recording of a past stream
+#[derive(Debug, Clone, Deserialize)]
+pub struct PastStream {
+    /// Unique id of the past stream.
+    pub past_stream_id: String,
+
+    /// Title of the stream.
+    #[serde(default)]
+    pub title: String,
+
+    /// Url to watch the recording.
+    #[serde(default)]
+    pub url: String,
+
+    /// Url of the recording's thumbnail.
+    #[serde(default)]
+    pub thumbnail: String,
+
+    /// Length of the recording in seconds.
+    #[serde(default)]
+    pub duration: u64,
+
+    /// Number of views.
+    #[serde(default)]
+    pub views: u64,
+
+    /// Language of the stream, in ISO 2 (2 letter language code).
+    #[serde(default)]
+    pub language: String,
+
+    /// Id of the category the stream was in.
+    #[serde(default)]
+    pub category_id: String,
+
+    /// Name of the category the stream was in.
+    #[serde(default)]
+    pub category_name: String,
+
+    /// User id of the streamer.
+    #[serde(default)]
+    pub streamer_id: String,
+
+    /// Username of the streamer.
+    #[serde(default)]
+    pub streamer_username: String,
+
+    /// When the stream started.
+    #[serde(with = "serde_with::chrono::datetime_utc_ts_seconds_from_any")]
+    pub created_at: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn audience_types_round_trip() {
+        for audience in [
+            AudienceType::FamilyFriendly,
+            AudienceType::Teen,
+            AudienceType::EighteenPlus,
+        ] {
+            let json = serde_json::to_string(&audience).unwrap();
+            assert_eq!(json, format!("\"{}\"", audience.as_str()));
+            assert_eq!(
+                serde_json::from_str::<AudienceType>(&json).unwrap(),
+                audience
+            );
+        }
+
+        let update = ChannelUpdate::new().with_audi_type(AudienceType::Teen);
+        assert_eq!(
+            serde_json::to_value(&update).unwrap(),
+            serde_json::json!({ "audi_type": "CHANNEL_AUDIENCE_TYPE_TEEN" })
+        );
+    }
+
+    #[test]
+    fn subscriber_paging_stops_at_the_offset_limit() {
+        let page: Subscribers = serde_json::from_str(
+            r#"{"total": "18446744073709551615", "subscriptions": [{
+                "user": {"user_id": "1", "created_at": "1600000000"},
+                "sub_created_at": "1600000000"
+            }]}"#,
+        )
+        .unwrap();
+        let query = SubscribersQuery {
+            offset: Some(u32::MAX - 1),
+            ..Default::default()
+        };
+        let next = page.next_page(&query).unwrap();
+        assert_eq!(next.offset, Some(u32::MAX));
+        assert!(page.next_page(&next).is_none());
+    }
+
+    fn emote(name: &str, url: &str) -> Emote {
+        Emote {
+            name: name.to_string(),
+            description: String::new(),
+            url: url.to_string(),
+            status: String::new(),
+            activity_name: None,
+            gifp: None,
+            webp: None,
+            update_time: None,
+        }
+    }
+
+    #[test]
+    fn emotes_are_found_by_code() {
+        let emotes = EmoteChannels {
+            customized_emotes: CustomizedEmotes {
+                channel: vec![ChannelEmotes {
+                    channel_id: "100".to_string(),
+                    emotes: vec![emote("happy", "channel.png")],
+                }],
+            },
+            event_emotes: Vec::new(),
+            global_emotes: vec![emote("happy", "global.png"), emote("sad", "sad.png")],
+        };
+        assert_eq!(emotes.find_by_code(":sad").unwrap().url, "sad.png");
+        assert!(emotes.find_by_code("sad").is_none());
+        assert_eq!(emotes.channel("100").unwrap().len(), 1);
+        assert_eq!(emotes.of_kind(EmoteKind::Global).count(), 2);
+        assert_eq!(emotes.of_kind(EmoteKind::Event).count(), 0);
+
+        let urls = emotes.urls_by_code();
+        assert_eq!(urls[":happy"], "channel.png");
+        assert_eq!(urls.len(), 2);
+    }
+}
@@ -1,4 +1,5 @@
-use reqwest::StatusCode;
+use crate::{Scope, TransportError};
+use http::StatusCode;
 use serde::{Deserialize, Serialize};
 use serde_repr::*;
 use std::fmt::{Debug, Display};
@@ -134,9 +135,17 @@ pub enum RequestError {
     #[error("bad request ({:?}): {}", .0.status, .0.message)]
     ApiError(ApiError),
 
-    /// Some other request error happened, could be status code, or network.
+    /// The request couldn't be sent or the response couldn't be read, such as a network error.
     #[error(transparent)]
-    Other(#[from] reqwest::Error),
+    Transport(#[from] TransportError),
+
+    /// The api responded with an unexpected status code.
+    #[error("unexpected status code {0}")]
+    Status(StatusCode),
+
+    /// The request or response body couldn't be (de)serialised.
+    #[error("invalid json: {0}")]
+    Json(#[from] serde_json::Error),
 }
 
 /// Standard errors that can occur on most api calls
@@ -149,14 +158,209 @@ where
     #[error("failed to refresh token: {0}")]
     RefreshToken(E),
 
+    /// The access token is missing a scope required by the endpoint, so the request wasn't sent
+    #[error("access token is missing the {0} scope")]
+    MissingScope(Scope),
+
+    /// An argument can't be sent to the endpoint, such as a username with whitespace in a chat
+    /// command, so the request wasn't sent
+    #[error("invalid argument: {0}")]
+    InvalidArgument(String),
+
     /// The api returned an error response. Can inspect the stats to found out what specifically
     /// went wrong.
     #[error("bad request ({:?}): {}", .0.status, .0.message)]
     ApiError(ApiError),
 
-    /// Some other request error happened, could be status code, or network.
+    /// The request couldn't be sent or the response couldn't be read, such as a network error.
+    #[error(transparent)]
+    Transport(#[from] TransportError),
+
+    /// The api responded with an unexpected status code.
+    #[error("unexpected status code {0}")]
+    Status(StatusCode),
+
+    /// The request or response body couldn't be (de)serialised.
+    #[error("invalid json: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+impl RequestError {
+    /// Returns true if the request failed for a reason that may be temporary, such as a
+    /// connection error, timeout, rate limiting or a server error, so is worth retrying.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::ApiError(err) => err.is_retryable(),
+            Self::Transport(err) => !err.is_circuit_open(),
+            Self::Status(status) => {
+                *status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+            }
+            Self::Json(_) => false,
+        }
+    }
+
+    /// Returns true if Trovo can't have handled the request, because the connection failed or
+    /// it was rate limited, so it's safe to retry even if it isn't idempotent
+    pub(crate) fn is_unhandled(&self) -> bool {
+        match self {
+            Self::Transport(err) => err.is_connect(),
+            Self::Status(status) => *status == StatusCode::TOO_MANY_REQUESTS,
+            Self::ApiError(_) | Self::Json(_) => false,
+        }
+    }
+
+    /// Id sent with the request in the `X-Request-Id` header, for api and transport errors.
+    ///
+    /// Every request's id is also recorded on its `trovo_request` tracing span.
+    pub fn request_id(&self) -> Option<&str> {
+        match self {
+            Self::ApiError(err) => err.request_id.as_deref(),
+            Self::Transport(err) => err.request_id(),
+            Self::Status(_) | Self::Json(_) => None,
+        }
+    }
+}
+
+impl<E> AuthenticatedRequestError<E>
+where
+    E: Display + Debug,
+{
+    /// Returns true if the request failed for a reason that may be temporary, see
+    /// [`RequestError::is_retryable`]. Refresh errors are never considered retryable.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::RefreshToken(_)
+            | Self::MissingScope(_)
+            | Self::InvalidArgument(_)
+            | Self::Json(_) => false,
+            Self::ApiError(err) => err.is_retryable(),
+            Self::Transport(err) => !err.is_circuit_open(),
+            Self::Status(status) => {
+                *status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+            }
+        }
+    }
+
+    /// Id sent with the request in the `X-Request-Id` header, see
+    /// [`RequestError::request_id`].
+    pub fn request_id(&self) -> Option<&str> {
+        match self {
+            Self::ApiError(err) => err.request_id.as_deref(),
+            Self::Transport(err) => err.request_id(),
+            _ => None,
+        }
+    }
+}
+
+impl<E> From<RequestError> for AuthenticatedRequestError<E>
+where
+    E: Display + Debug,
+{
+    fn from(error: RequestError) -> Self {
+        match error {
+            RequestError::ApiError(err) => Self::ApiError(err),
+            RequestError::Transport(err) => Self::Transport(err),
+            RequestError::Status(status) => Self::Status(status),
+            RequestError::Json(err) => Self::Json(err),
+        }
+    }
+}
+
+/// Errors that can occur when exchanging an authorization code or refreshing a token, categorised
+/// so callers can decide whether to retry or send the user to reauthorize.
+#[derive(Debug, Error)]
+pub enum OAuthError {
+    /// The authorization code or refresh token is invalid, expired or already used. The user
+    /// needs to authorize the app again.
+    #[error("invalid grant ({:?}): {}", .0.status, .0.message)]
+    InvalidGrant(ApiError),
+
+    /// The client id, client secret or redirect uri were rejected.
+    #[error("invalid client ({:?}): {}", .0.status, .0.message)]
+    InvalidClient(ApiError),
+
+    /// Too many requests or tokens, try again later.
+    #[error("rate limited ({:?}): {}", .0.status, .0.message)]
+    RateLimited(ApiError),
+
+    /// The api returned some other error response.
+    #[error("bad request ({:?}): {}", .0.status, .0.message)]
+    ApiError(ApiError),
+
+    /// The request failed to send or the response couldn't be read.
     #[error(transparent)]
-    Other(#[from] reqwest::Error),
+    Network(#[from] TransportError),
+
+    /// The api responded with an unexpected status code.
+    #[error("unexpected status code {0}")]
+    Status(StatusCode),
+
+    /// The response body couldn't be deserialised.
+    #[error("invalid json: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+impl OAuthError {
+    /// Returns true if the grant is permanently invalid and the user has to authorize the app
+    /// again, rather than the request being worth retrying.
+    pub fn requires_reauthorization(&self) -> bool {
+        matches!(self, Self::InvalidGrant(_))
+    }
+
+    /// Id sent with the request in the `X-Request-Id` header, see
+    /// [`RequestError::request_id`].
+    pub fn request_id(&self) -> Option<&str> {
+        match self {
+            Self::InvalidGrant(err)
+            | Self::InvalidClient(err)
+            | Self::RateLimited(err)
+            | Self::ApiError(err) => err.request_id.as_deref(),
+            Self::Network(err) => err.request_id(),
+            Self::Status(_) | Self::Json(_) => None,
+        }
+    }
+
+    /// Returns true if the failure is likely temporary and the request can be retried later.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Self::RateLimited(_) | Self::Network(_) => true,
+            Self::Status(status) => status.is_server_error(),
+            Self::ApiError(err) => err.is_retryable(),
+            _ => false,
+        }
+    }
+}
+
+impl From<ApiError> for OAuthError {
+    fn from(err: ApiError) -> Self {
+        match err.status {
+            ErrorStatus::InvalidAuthCode1
+            | ErrorStatus::InvalidAuthCode2
+            | ErrorStatus::UsedAuthCode
+            | ErrorStatus::RefreshTokenExpired
+            | ErrorStatus::InvalidRefreshToken
+            | ErrorStatus::AccessTokenExpired => Self::InvalidGrant(err),
+            ErrorStatus::InvalidGrantType
+            | ErrorStatus::InvalidRedirectUri
+            | ErrorStatus::InvalidClientSecret
+            | ErrorStatus::InvalidHeader => Self::InvalidClient(err),
+            ErrorStatus::RateLimitExceeded | ErrorStatus::AccessTokenLimit => {
+                Self::RateLimited(err)
+            }
+            _ => Self::ApiError(err),
+        }
+    }
+}
+
+impl From<RequestError> for OAuthError {
+    fn from(error: RequestError) -> Self {
+        match error {
+            RequestError::ApiError(err) => err.into(),
+            RequestError::Transport(err) => Self::Network(err),
+            RequestError::Status(status) => Self::Status(status),
+            RequestError::Json(err) => Self::Json(err),
+        }
+    }
 }
 
 /// Struct representing errors that trovo api responds with.
@@ -167,6 +371,14 @@ pub struct ApiError {
 
     /// Trovo provided message describing the error
     pub message: String,
+
+    /// Id sent with the request in the `X-Request-Id` header, to correlate the error with logs
+    #[serde(skip)]
+    pub request_id: Option<String>,
+
+    /// Http status code of the response the error was returned with
+    #[serde(skip)]
+    pub http_status: Option<StatusCode>,
 }
 
 impl ApiError {
@@ -176,6 +388,30 @@ impl ApiError {
             || status == StatusCode::UNAUTHORIZED
             || status == StatusCode::INTERNAL_SERVER_ERROR
     }
+
+    /// Returns true if the access token was rejected as invalid or expired
+    pub fn is_invalid_token(&self) -> bool {
+        matches!(
+            self.status,
+            ErrorStatus::AuthorizationFailed
+                | ErrorStatus::InvalidAccessToken
+                | ErrorStatus::AccessTokenExpired
+        )
+    }
+
+    /// Returns true if the error is an internal server error or rate limit, so the request is
+    /// worth retrying.
+    pub fn is_retryable(&self) -> bool {
+        matches!(self.http_status, Some(status) if status.is_server_error())
+            || matches!(
+                self.status,
+                ErrorStatus::InternalFetch
+                    | ErrorStatus::InternalTimeout
+                    | ErrorStatus::InternalUnknown
+                    | ErrorStatus::Conflict
+                    | ErrorStatus::RateLimitExceeded
+            )
+    }
 }
 
 impl Default for ApiError {
@@ -183,6 +419,8 @@ impl Default for ApiError {
         Self {
             status: ErrorStatus::Unknown,
             message: "Unknown or uncategorized error".to_string(),
+            request_id: None,
+            http_status: None,
         }
     }
 }
@@ -192,3 +430,66 @@ impl Default for ApiError {
 #[derive(Error, Debug)]
 #[error("access token expired and doesn't support refreshing")]
 pub struct AccessTokenExpired;
+
+/// Error returned by [`ClientBuilder::proxy`](crate::ClientBuilder::proxy) for urls that can't
+/// be used as a proxy.
+#[derive(Error, Debug)]
+#[error("invalid proxy url: {0}")]
+pub struct InvalidProxy(String);
+
+impl InvalidProxy {
+    pub(crate) fn new(reason: impl Into<String>) -> Self {
+        Self(reason.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn api_error(status: ErrorStatus) -> ApiError {
+        ApiError {
+            status,
+            message: String::new(),
+            request_id: None,
+            http_status: None,
+        }
+    }
+
+    #[test]
+    fn classify_oauth_errors() {
+        let err = OAuthError::from(api_error(ErrorStatus::RefreshTokenExpired));
+        assert!(err.requires_reauthorization());
+        assert!(!err.is_transient());
+
+        let err = OAuthError::from(api_error(ErrorStatus::InvalidClientSecret));
+        assert!(matches!(err, OAuthError::InvalidClient(_)));
+        assert!(!err.requires_reauthorization());
+
+        let err = OAuthError::from(api_error(ErrorStatus::RateLimitExceeded));
+        assert!(err.is_transient());
+        assert!(!err.requires_reauthorization());
+
+        let err = OAuthError::from(api_error(ErrorStatus::InternalTimeout));
+        assert!(matches!(err, OAuthError::ApiError(_)));
+        assert!(err.is_transient());
+    }
+
+    #[test]
+    fn invalid_token_errors() {
+        assert!(api_error(ErrorStatus::AccessTokenExpired).is_invalid_token());
+        assert!(api_error(ErrorStatus::InvalidAccessToken).is_invalid_token());
+        assert!(!api_error(ErrorStatus::RateLimitExceeded).is_invalid_token());
+    }
+
+    #[test]
+    fn server_errors_are_retryable() {
+        let mut err = api_error(ErrorStatus::InvalidParameters);
+        assert!(!err.is_retryable());
+        err.http_status = Some(StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(err.is_retryable());
+        assert!(OAuthError::from(err).is_transient());
+
+        assert!(RequestError::Status(StatusCode::INTERNAL_SERVER_ERROR).is_retryable());
+    }
+}
@@ -0,0 +1,64 @@
+use crate::RequestError;
+use http::{Method, StatusCode};
+use std::{fmt::Debug, time::Duration};
+
+/// Callbacks for each api request the client sends, such as to record metrics, set with
+/// [`ClientBuilder::hooks`](crate::ClientBuilder::hooks).
+///
+/// All methods do nothing by default. They're called synchronously while the request is being
+/// sent, so should return quickly.
+///
+/// ```
+/// use std::time::Duration;
+/// use trovo::{ClientHooks, RequestInfo, RequestOutcome};
+///
+/// #[derive(Debug)]
+/// struct Metrics;
+///
+/// impl ClientHooks for Metrics {
+///     fn on_request_end(&self, request: &RequestInfo, outcome: &RequestOutcome<'_>) {
+///         println!(
+///             "{} {} took {:?} (status {:?}, attempt {})",
+///             request.method, request.path, outcome.duration, outcome.status, request.attempt
+///         );
+///     }
+/// }
+/// ```
+pub trait ClientHooks: Debug + Send + Sync {
+    /// Called before each attempt at a request is sent, after waiting for any rate limits
+    fn on_request_start(&self, _request: &RequestInfo) {}
+
+    /// Called when an attempt at a request finishes, successfully or not
+    fn on_request_end(&self, _request: &RequestInfo, _outcome: &RequestOutcome<'_>) {}
+
+    /// Called when a failed attempt is going to be retried after `backoff`. `request.attempt` is
+    /// the number of the attempt that failed.
+    fn on_retry(&self, _request: &RequestInfo, _error: &RequestError, _backoff: Duration) {}
+}
+
+/// The request that a [`ClientHooks`] method is called for
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct RequestInfo {
+    /// Http method of the request
+    pub method: Method,
+    /// Path of the request's url, such as `/openplatform/getusers`
+    pub path: String,
+    /// Id sent with the request in the `X-Request-Id` header
+    pub request_id: String,
+    /// Which attempt at the request this is, starting from 1. Only above 1 when retries are
+    /// enabled.
+    pub attempt: u32,
+}
+
+/// How an attempt at a request went, passed to [`ClientHooks::on_request_end`]
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct RequestOutcome<'a> {
+    /// Status code of the response, if one was received and it wasn't an api error
+    pub status: Option<StatusCode>,
+    /// How long the attempt took, from sending the request to reading the whole response
+    pub duration: Duration,
+    /// The error the attempt failed with, if it failed
+    pub error: Option<&'a RequestError>,
+}
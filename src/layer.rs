@@ -0,0 +1,148 @@
+use crate::{HttpRequest, HttpResponse, HttpTransport, TransportError};
+use std::{fmt::Debug, sync::Arc};
+
+/// Middleware that can inspect and modify requests and responses, added with
+/// [`ClientBuilder::with_layer`](crate::ClientBuilder::with_layer).
+///
+/// Layers run in the order they were added, each calling [`Next::run`] to pass the request on
+/// to the next layer and finally the transport. A layer can return a response without calling
+/// `next` to short-circuit the request, such as to serve it from a cache.
+///
+/// ```
+/// use trovo::{HttpRequest, HttpResponse, Layer, Next, TransportError};
+///
+/// #[derive(Debug)]
+/// struct UserAgent;
+///
+/// #[async_trait::async_trait]
+/// impl Layer for UserAgent {
+///     async fn handle(
+///         &self,
+///         mut request: HttpRequest,
+///         next: Next<'_>,
+///     ) -> Result<HttpResponse, TransportError> {
+///         request
+///             .headers_mut()
+///             .insert("User-Agent", "my-bot/1.0".parse().unwrap());
+///         next.run(request).await
+///     }
+/// }
+/// ```
+#[async_trait::async_trait]
+pub trait Layer: Debug + Send + Sync {
+    /// Handle the request, usually by passing it on to `next`
+    async fn handle(
+        &self,
+        request: HttpRequest,
+        next: Next<'_>,
+    ) -> Result<HttpResponse, TransportError>;
+}
+
+/// The rest of the layers and the transport that a request is passed on to
+#[derive(Debug)]
+pub struct Next<'a> {
+    layers: &'a [Arc<dyn Layer>],
+    transport: &'a dyn HttpTransport,
+}
+
+impl Next<'_> {
+    /// Pass the request on to the next layer, or the transport if this is the last layer
+    pub async fn run(self, request: HttpRequest) -> Result<HttpResponse, TransportError> {
+        match self.layers.split_first() {
+            Some((layer, layers)) => {
+                let next = Next {
+                    layers,
+                    transport: self.transport,
+                };
+                layer.handle(request, next).await
+            }
+            None => self.transport.execute(request).await,
+        }
+    }
+}
+
+/// A transport that passes requests through layers before sending them
+#[derive(Debug)]
+pub(crate) struct Layered {
+    pub(crate) layers: Vec<Arc<dyn Layer>>,
+    pub(crate) transport: Arc<dyn HttpTransport>,
+}
+
+#[async_trait::async_trait]
+impl HttpTransport for Layered {
+    async fn execute(&self, request: HttpRequest) -> Result<HttpResponse, TransportError> {
+        let next = Next {
+            layers: &self.layers,
+            transport: &*self.transport,
+        };
+        next.run(request).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug)]
+    struct EchoHeader;
+
+    #[async_trait::async_trait]
+    impl HttpTransport for EchoHeader {
+        async fn execute(&self, request: HttpRequest) -> Result<HttpResponse, TransportError> {
+            let body = request
+                .headers()
+                .get("x-test")
+                .map(|value| value.as_bytes().to_vec())
+                .unwrap_or_default();
+            Ok(http::Response::new(body))
+        }
+    }
+
+    #[derive(Debug)]
+    struct AddHeader;
+
+    #[async_trait::async_trait]
+    impl Layer for AddHeader {
+        async fn handle(
+            &self,
+            mut request: HttpRequest,
+            next: Next<'_>,
+        ) -> Result<HttpResponse, TransportError> {
+            request
+                .headers_mut()
+                .insert("x-test", "layered".parse().unwrap());
+            next.run(request).await
+        }
+    }
+
+    #[derive(Debug)]
+    struct ShortCircuit;
+
+    #[async_trait::async_trait]
+    impl Layer for ShortCircuit {
+        async fn handle(
+            &self,
+            _request: HttpRequest,
+            _next: Next<'_>,
+        ) -> Result<HttpResponse, TransportError> {
+            Ok(http::Response::new(b"cached".to_vec()))
+        }
+    }
+
+    #[tokio::test]
+    async fn layers_run_in_order() {
+        let transport = Layered {
+            layers: vec![Arc::new(AddHeader)],
+            transport: Arc::new(EchoHeader),
+        };
+        let res = transport.execute(http::Request::new(vec![])).await.unwrap();
+        assert_eq!(res.body(), b"layered");
+
+        let transport = Layered {
+            layers: vec![Arc::new(ShortCircuit), Arc::new(AddHeader)],
+            transport: Arc::new(EchoHeader),
+        };
+        let res = transport.execute(http::Request::new(vec![])).await.unwrap();
+        assert_eq!(res.body(), b"cached");
+    }
+}
@@ -41,18 +41,71 @@
 //!     Ok(())
 //! }
 //! ```
+//!
+//! ## Features
+//!
+//! - `chat` (default): connect to chat via websockets
+//! - `rustls-tls` (default): use rustls for TLS
+//! - `native-tls`: use the platform's native TLS, such as OpenSSL on linux. Disable default
+//!   features to build without rustls
+//! - `socks`: support `socks5://` proxies for api requests
+//! - `gzip` (default), `brotli` (default), `deflate`: accept compressed api responses. Only
+//!   applies to the default reqwest client
+//! - `blocking`: a blocking client in `trovo::blocking`
+//! - `redirect-server`: `RedirectServer` to capture OAuth redirects locally
+//! - `secrecy`: conversions between [`Secret`] and `secrecy::SecretString`
+//!
+//! ## WebAssembly
+//!
+//! The api client builds for `wasm32-unknown-unknown` with default features disabled, sending
+//! requests with the browser's fetch api. Chat, `JsonFileTokenStorage`, `spawn_refresh_task`
+//! and the `blocking` and `redirect-server` features aren't available on wasm, and neither are
+//! proxies, connect timeouts or custom root certificates.
 
 mod auth;
+mod batch;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+mod builder;
+mod cache;
 #[cfg(feature = "chat")]
 pub mod chat;
+mod circuit_breaker;
 mod client;
+mod coalesce;
+mod deadline;
 mod entities;
 mod errors;
+mod hooks;
+mod layer;
+mod paginate;
+mod rate_limit;
+mod raw;
+mod response;
+mod retry;
+mod time;
+mod transport;
 
 pub use auth::*;
+pub use batch::*;
+pub use builder::*;
+pub use cache::*;
+pub use circuit_breaker::*;
 pub use client::*;
+pub use coalesce::*;
+pub use deadline::*;
 pub use entities::*;
 pub use errors::*;
+pub use hooks::*;
+pub use layer::*;
+pub use paginate::*;
+pub use rate_limit::*;
+pub use raw::*;
+pub use response::*;
+pub use retry::*;
+pub use transport::*;
+
+pub use http;
 
 #[macro_use]
 extern crate tracing;
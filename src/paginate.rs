@@ -0,0 +1,235 @@
+use crate::{
+    AccessTokenProvider, AuthenticatedRequestError, Client, ClientIdProvider, Clip, Clips,
+    ClipsQuery, Follower, Followers, FollowersQuery, PastStream, PastStreams, PastStreamsQuery,
+    RequestError, Subscriber, Subscribers, SubscribersQuery, TopChannel, TopChannels,
+    TopChannelsQuery,
+};
+use futures::{
+    stream::{self, BoxStream},
+    Future, Stream, StreamExt, TryStreamExt,
+};
+use std::{
+    fmt::{self, Debug},
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// A page of a list endpoint that knows how to request the next page
+pub(crate) trait Page {
+    type Query: Clone;
+    type Item;
+
+    fn next_page(&self, query: &Self::Query) -> Option<Self::Query>;
+
+    fn into_items(self) -> Vec<Self::Item>;
+}
+
+macro_rules! page {
+    ($page:ty, $query:ty, $item:ty, $items:ident) => {
+        impl Page for $page {
+            type Query = $query;
+            type Item = $item;
+
+            fn next_page(&self, query: &Self::Query) -> Option<Self::Query> {
+                <$page>::next_page(self, query)
+            }
+
+            fn into_items(self) -> Vec<Self::Item> {
+                self.$items
+            }
+        }
+    };
+}
+
+page!(TopChannels, TopChannelsQuery, TopChannel, channels);
+page!(Followers, FollowersQuery, Follower, followers);
+page!(Subscribers, SubscribersQuery, Subscriber, subscribers);
+page!(Clips, ClipsQuery, Clip, clips);
+page!(PastStreams, PastStreamsQuery, PastStream, past_streams);
+
+/// A [`Stream`] of every item of a list endpoint, requesting each page as the previous one runs
+/// out.
+///
+/// The stream ends after the last page, or after the first error.
+///
+/// ```no_run
+/// # async fn run(client: trovo::Client<trovo::ClientId>) -> Result<(), trovo::RequestError> {
+/// use futures::TryStreamExt;
+/// use trovo::FollowersQuery;
+///
+/// let followers: Vec<_> = client
+///     .channel_followers_paginated("channel id", FollowersQuery::default())
+///     .try_collect()
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[must_use = "streams do nothing unless polled"]
+pub struct Paginated<'a, T, E> {
+    inner: BoxStream<'a, Result<T, E>>,
+}
+
+impl<'a, T, E> Paginated<'a, T, E> {
+    /// Page through the endpoint, starting from `query`
+    pub(crate) fn new<P, F, Fut>(query: P::Query, fetch: F) -> Self
+    where
+        P: Page<Item = T> + Send + 'a,
+        P::Query: Send + 'a,
+        T: Send + 'a,
+        E: Send + 'a,
+        F: Fn(P::Query) -> Fut + Send + 'a,
+        Fut: Future<Output = Result<P, E>> + Send + 'a,
+    {
+        let pages = stream::unfold(Some((query, fetch)), |state| async move {
+            let (query, fetch) = state?;
+            match fetch(query.clone()).await {
+                Ok(page) => {
+                    let next = page.next_page(&query).map(|next| (next, fetch));
+                    Some((Ok(page.into_items()), next))
+                }
+                Err(err) => Some((Err(err), None)),
+            }
+        });
+        Self {
+            inner: pages
+                .map_ok(|items| stream::iter(items.into_iter().map(Ok)))
+                .try_flatten()
+                .boxed(),
+        }
+    }
+}
+
+impl<T, E> Stream for Paginated<'_, T, E> {
+    type Item = Result<T, E>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.poll_next_unpin(cx)
+    }
+}
+
+impl<T, E> Debug for Paginated<'_, T, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Paginated").finish_non_exhaustive()
+    }
+}
+
+impl<A> Client<A>
+where
+    A: ClientIdProvider + Send + Sync,
+{
+    /// Like [`Client::top_channels`], but streams the channels of every page.
+    pub fn top_channels_paginated(
+        &self,
+        query: TopChannelsQuery,
+    ) -> Paginated<'_, TopChannel, RequestError> {
+        Paginated::new(query, move |query| async move {
+            self.top_channels(&query).await
+        })
+    }
+
+    /// Stream every channel live in a category right now, with the most viewers first.
+    ///
+    /// Built on [`Client::top_channels`], fetching `page_size` channels per request (up to 100).
+    /// The api only sorts channels by viewers.
+    pub fn channels_in_category(
+        &self,
+        category_id: impl Into<String>,
+        page_size: u32,
+    ) -> Paginated<'_, TopChannel, RequestError> {
+        self.top_channels_paginated(TopChannelsQuery {
+            limit: Some(page_size),
+            category_id: Some(category_id.into()),
+            ..Default::default()
+        })
+    }
+
+    /// Like [`Client::channel_followers`], but streams the followers of every page.
+    pub fn channel_followers_paginated(
+        &self,
+        channel_id: impl Into<String>,
+        query: FollowersQuery,
+    ) -> Paginated<'_, Follower, RequestError> {
+        let channel_id = channel_id.into();
+        Paginated::new(query, move |query| {
+            let channel_id = channel_id.clone();
+            async move { self.channel_followers(channel_id, &query).await }
+        })
+    }
+
+    /// Like [`Client::clips`], but streams the clips of every page.
+    pub fn clips_paginated(
+        &self,
+        channel_id: impl Into<String>,
+        query: ClipsQuery,
+    ) -> Paginated<'_, Clip, RequestError> {
+        let channel_id = channel_id.into();
+        Paginated::new(query, move |query| {
+            let channel_id = channel_id.clone();
+            async move { self.clips(channel_id, &query).await }
+        })
+    }
+
+    /// Like [`Client::past_streams`], but streams the past streams of every page.
+    pub fn past_streams_paginated(
+        &self,
+        channel_id: impl Into<String>,
+        query: PastStreamsQuery,
+    ) -> Paginated<'_, PastStream, RequestError> {
+        let channel_id = channel_id.into();
+        Paginated::new(query, move |query| {
+            let channel_id = channel_id.clone();
+            async move { self.past_streams(channel_id, &query).await }
+        })
+    }
+}
+
+impl<A> Client<A>
+where
+    A: AccessTokenProvider + Send + Sync,
+    A::Error: Send,
+{
+    /// Like [`Client::channel_subscribers`], but streams the subscribers of every page.
+    pub fn channel_subscribers_paginated(
+        &self,
+        channel_id: impl Into<String>,
+        query: SubscribersQuery,
+    ) -> Paginated<'_, Subscriber, AuthenticatedRequestError<A::Error>> {
+        let channel_id = channel_id.into();
+        Paginated::new(query, move |query| {
+            let channel_id = channel_id.clone();
+            async move { self.channel_subscribers(channel_id, &query).await }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+
+    #[derive(Debug)]
+    struct Numbers(Vec<u32>, Option<u32>);
+
+    impl Page for Numbers {
+        type Query = u32;
+        type Item = u32;
+
+        fn next_page(&self, _query: &u32) -> Option<u32> {
+            self.1
+        }
+
+        fn into_items(self) -> Vec<u32> {
+            self.0
+        }
+    }
+
+    #[test]
+    fn follows_pages_until_the_last() {
+        let stream: Paginated<'_, u32, ()> = Paginated::new(0, |page: u32| async move {
+            let next = if page < 2 { Some(page + 1) } else { None };
+            Ok(Numbers(vec![page * 10, page * 10 + 1], next))
+        });
+        let items: Vec<_> = block_on(stream.try_collect::<Vec<_>>()).unwrap();
+        assert_eq!(items, [0, 1, 10, 11, 20, 21]);
+    }
+}
@@ -0,0 +1,187 @@
+use crate::time::{sleep, Instant};
+use std::{sync::Mutex, time::Duration};
+
+/// A limit of `requests` per `period`, allowing bursts of up to `requests` at once.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimit {
+    requests: u32,
+    period: Duration,
+}
+
+impl RateLimit {
+    /// Allow `requests` requests every `period`
+    pub fn new(requests: u32, period: Duration) -> Self {
+        Self { requests, period }
+    }
+
+    /// Allow `requests` requests every second
+    pub fn per_second(requests: u32) -> Self {
+        Self::new(requests, Duration::from_secs(1))
+    }
+
+    /// Allow `requests` requests every minute
+    pub fn per_minute(requests: u32) -> Self {
+        Self::new(requests, Duration::from_secs(60))
+    }
+}
+
+/// Rate limits applied by the client before sending requests, set with
+/// [`ClientBuilder::rate_limits`](crate::ClientBuilder::rate_limits).
+///
+/// Requests over the limit wait until they can be sent rather than failing. The defaults match
+/// Trovo's documented limits, raise them if your app has been granted a higher quota.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RateLimits {
+    api: RateLimit,
+    chat_send: RateLimit,
+}
+
+impl Default for RateLimits {
+    fn default() -> Self {
+        Self {
+            api: RateLimit::per_minute(1200),
+            chat_send: RateLimit::per_second(1),
+        }
+    }
+}
+
+impl RateLimits {
+    /// Create the default rate limits
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the limit for api requests other than sending chat messages. Defaults to 1200
+    /// requests per minute.
+    pub fn api(mut self, limit: RateLimit) -> Self {
+        self.api = limit;
+        self
+    }
+
+    /// Sets the limit for sending chat messages. Defaults to 1 message per second, the limit
+    /// for users that aren't moderators of the channel.
+    pub fn chat_send(mut self, limit: RateLimit) -> Self {
+        self.chat_send = limit;
+        self
+    }
+}
+
+/// Category of endpoint that a rate limit applies to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum RateLimitCategory {
+    Api,
+    ChatSend,
+}
+
+impl RateLimitCategory {
+    /// Category of the endpoint at the given path
+    pub(crate) fn for_path(path: &str) -> Self {
+        if path.ends_with("/chat/send") {
+            Self::ChatSend
+        } else {
+            Self::Api
+        }
+    }
+}
+
+/// Token buckets for each [`RateLimitCategory`]
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    api: Bucket,
+    chat_send: Bucket,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(limits: &RateLimits) -> Self {
+        Self {
+            api: Bucket::new(limits.api),
+            chat_send: Bucket::new(limits.chat_send),
+        }
+    }
+
+    /// Wait until a request in the given category can be sent
+    pub(crate) async fn acquire(&self, category: RateLimitCategory) {
+        let bucket = match category {
+            RateLimitCategory::Api => &self.api,
+            RateLimitCategory::ChatSend => &self.chat_send,
+        };
+        while let Some(wait) = bucket.try_acquire(Instant::now()) {
+            trace!(?category, ?wait, "rate limited, waiting");
+            sleep(wait).await;
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    capacity: f64,
+    tokens_per_sec: f64,
+    state: Mutex<BucketState>,
+}
+
+#[derive(Debug)]
+struct BucketState {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    fn new(limit: RateLimit) -> Self {
+        let capacity = f64::from(limit.requests.max(1));
+        Self {
+            capacity,
+            tokens_per_sec: capacity / limit.period.as_secs_f64().max(f64::EPSILON),
+            state: Mutex::new(BucketState {
+                tokens: capacity,
+                updated: Instant::now(),
+            }),
+        }
+    }
+
+    /// Take a token if there is one, otherwise returns how long until there will be
+    fn try_acquire(&self, now: Instant) -> Option<Duration> {
+        let mut state = self.state.lock().unwrap();
+        let elapsed = now.saturating_duration_since(state.updated).as_secs_f64();
+        state.tokens = (state.tokens + elapsed * self.tokens_per_sec).min(self.capacity);
+        state.updated = now;
+
+        if state.tokens >= 1.0 {
+            state.tokens -= 1.0;
+            None
+        } else {
+            Some(Duration::from_secs_f64(
+                (1.0 - state.tokens) / self.tokens_per_sec,
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bucket_refills() {
+        let bucket = Bucket::new(RateLimit::per_second(2));
+        let start = Instant::now();
+        assert_eq!(bucket.try_acquire(start), None);
+        assert_eq!(bucket.try_acquire(start), None);
+
+        let wait = bucket.try_acquire(start).unwrap();
+        assert!(wait > Duration::from_millis(490) && wait <= Duration::from_millis(500));
+        assert_eq!(bucket.try_acquire(start + wait), None);
+        assert!(bucket.try_acquire(start + wait).is_some());
+    }
+
+    #[test]
+    fn categories() {
+        assert_eq!(
+            RateLimitCategory::for_path("/openplatform/chat/send"),
+            RateLimitCategory::ChatSend
+        );
+        assert_eq!(
+            RateLimitCategory::for_path("/openplatform/getusers"),
+            RateLimitCategory::Api
+        );
+    }
+}
@@ -0,0 +1,200 @@
+use crate::{
+    response::parse_with_meta, transport::BuilderExt, AccessTokenProvider,
+    AuthenticatedRequestError, Client, ClientIdProvider, HttpRequest, RequestError, WithMeta,
+};
+use http::Method;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+impl<A> Client<A> {
+    /// Call api endpoints that this crate doesn't support yet.
+    ///
+    /// ```no_run
+    /// # async fn run(client: trovo::Client<trovo::AccessTokenOnly>) -> Result<(), Box<dyn std::error::Error>> {
+    /// let res = client
+    ///     .raw()
+    ///     .post("getusers", &[], &serde_json::json!({ "user": ["username"] }))
+    ///     .await?;
+    /// println!("{} {:#}", res.meta.status(), res.data);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn raw(&self) -> Raw<'_, A> {
+        Raw { client: self }
+    }
+}
+
+impl<A> Client<A>
+where
+    A: ClientIdProvider,
+{
+    /// Like [`Client::call`], but with only the client id, for endpoints that don't need an
+    /// access token.
+    pub async fn public_call<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        body: &impl Serialize,
+    ) -> Result<T, RequestError> {
+        Ok(self.public_call_with_meta(method, path, body).await?.data)
+    }
+
+    /// Like [`Client::public_call`], but also returns the response metadata.
+    pub async fn public_call_with_meta<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        body: &impl Serialize,
+    ) -> Result<WithMeta<T>, RequestError> {
+        let req = self.raw().typed_request(method, path, body)?;
+        parse_with_meta(self.send(req).await?)
+    }
+}
+
+impl<A> Client<A>
+where
+    A: AccessTokenProvider,
+{
+    /// Call an api endpoint that this crate doesn't support yet, parsing the response into your
+    /// own type.
+    ///
+    /// Unlike [`Client::raw`] the response is deserialized straight into `T`, while auth,
+    /// retries and api errors are handled the same as for the built in endpoints. The body is
+    /// sent as json, unless it serializes to `null` such as `&()`, which sends no body.
+    ///
+    /// ```no_run
+    /// # async fn run(client: trovo::Client<trovo::AccessTokenOnly>) -> Result<(), Box<dyn std::error::Error>> {
+    /// use trovo::http::Method;
+    ///
+    /// #[derive(serde::Deserialize)]
+    /// struct Channel {
+    ///     channel_id: String,
+    /// }
+    ///
+    /// let channel: Channel = client.call(Method::GET, "channel", &()).await?;
+    /// println!("{}", channel.channel_id);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn call<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        body: &impl Serialize,
+    ) -> Result<T, AuthenticatedRequestError<A::Error>> {
+        Ok(self.call_with_meta(method, path, body).await?.data)
+    }
+
+    /// Like [`Client::call`], but also returns the response metadata.
+    pub async fn call_with_meta<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        body: &impl Serialize,
+    ) -> Result<WithMeta<T>, AuthenticatedRequestError<A::Error>> {
+        let req = self.raw().typed_request(method, path, body)?;
+        Ok(parse_with_meta(self.send_authenticated(req).await?)?)
+    }
+}
+
+/// Raw access to the api, created with [`Client::raw`].
+///
+/// Requests go through the client's transport, retries and rate limits like any other call, and
+/// api errors are returned as [`RequestError::ApiError`]. Paths are relative to the api base
+/// url, such as `getusers`.
+#[derive(Debug)]
+pub struct Raw<'a, A> {
+    client: &'a Client<A>,
+}
+
+impl<A> Raw<'_, A>
+where
+    A: ClientIdProvider,
+{
+    fn request(
+        &self,
+        method: Method,
+        path: &str,
+        query: &[(&str, &str)],
+        body: Option<&Value>,
+    ) -> Result<HttpRequest, RequestError> {
+        let mut path = path.trim_start_matches('/').to_string();
+        if !query.is_empty() {
+            path.push('?');
+            path.push_str(
+                &url::form_urlencoded::Serializer::new(String::new())
+                    .extend_pairs(query)
+                    .finish(),
+            );
+        }
+        let builder = self.client.request(method, &path);
+        match body {
+            Some(body) => builder.json(body),
+            None => builder.empty(),
+        }
+    }
+
+    /// Build a request for [`Client::call`], skipping `null` bodies
+    fn typed_request(
+        &self,
+        method: Method,
+        path: &str,
+        body: &impl Serialize,
+    ) -> Result<HttpRequest, RequestError> {
+        let body = serde_json::to_value(body)?;
+        let body = if body.is_null() { None } else { Some(&body) };
+        self.request(method, path, &[], body)
+    }
+
+    /// Send a `GET` request with only the client id, for endpoints that don't need an access
+    /// token.
+    pub async fn public_get(
+        &self,
+        path: &str,
+        query: &[(&str, &str)],
+    ) -> Result<WithMeta<Value>, RequestError> {
+        let req = self.request(Method::GET, path, query, None)?;
+        parse_with_meta(self.client.send(req).await?)
+    }
+
+    /// Send a `POST` request with the given json body and only the client id, for endpoints
+    /// that don't need an access token.
+    pub async fn public_post(
+        &self,
+        path: &str,
+        query: &[(&str, &str)],
+        body: &impl Serialize,
+    ) -> Result<WithMeta<Value>, RequestError> {
+        let body = serde_json::to_value(body)?;
+        let req = self.request(Method::POST, path, query, Some(&body))?;
+        parse_with_meta(self.client.send(req).await?)
+    }
+}
+
+impl<A> Raw<'_, A>
+where
+    A: AccessTokenProvider,
+{
+    /// Send a `GET` request authenticated with the auth provider's access token.
+    pub async fn get(
+        &self,
+        path: &str,
+        query: &[(&str, &str)],
+    ) -> Result<WithMeta<Value>, AuthenticatedRequestError<A::Error>> {
+        let req = self.request(Method::GET, path, query, None)?;
+        Ok(parse_with_meta(self.client.send_authenticated(req).await?)?)
+    }
+
+    /// Send a `POST` request with the given json body, authenticated with the auth provider's
+    /// access token.
+    pub async fn post(
+        &self,
+        path: &str,
+        query: &[(&str, &str)],
+        body: &impl Serialize,
+    ) -> Result<WithMeta<Value>, AuthenticatedRequestError<A::Error>> {
+        let body = serde_json::to_value(body).map_err(RequestError::from)?;
+        let req = self.request(Method::POST, path, query, Some(&body))?;
+        Ok(parse_with_meta(self.client.send_authenticated(req).await?)?)
+    }
+}
@@ -0,0 +1,137 @@
+use crate::{transport, HttpResponse, RequestError};
+use http::{HeaderMap, StatusCode};
+use serde::de::DeserializeOwned;
+use std::time::Duration;
+
+/// Headers that may carry the id Trovo assigned to a request, in order of preference
+const REQUEST_ID_HEADERS: [&str; 3] = ["x-request-id", "x-trace-id", "x-amzn-requestid"];
+
+/// The result of an api call along with metadata about its response, returned by the
+/// `*_with_meta` methods of [`Client`](crate::Client).
+#[derive(Debug, Clone)]
+pub struct WithMeta<T> {
+    /// The parsed response
+    pub data: T,
+    /// Status and headers of the response
+    pub meta: ResponseMeta,
+}
+
+impl<T> WithMeta<T> {
+    /// Transform the parsed response, keeping the metadata
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> WithMeta<U> {
+        WithMeta {
+            data: f(self.data),
+            meta: self.meta,
+        }
+    }
+
+    /// Discard the metadata
+    pub fn into_data(self) -> T {
+        self.data
+    }
+}
+
+/// Status and headers of an api response, for telemetry and keeping track of rate limits.
+#[derive(Debug, Clone)]
+pub struct ResponseMeta {
+    status: StatusCode,
+    headers: HeaderMap,
+}
+
+impl ResponseMeta {
+    /// Status code of the response
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// All headers of the response
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// Id of the request, if Trovo sent one in an `X-Request-Id` or similar header
+    pub fn request_id(&self) -> Option<&str> {
+        REQUEST_ID_HEADERS.iter().find_map(|name| self.header(name))
+    }
+
+    /// Rate limit quota from the `X-RateLimit-*` headers, if the response has them
+    pub fn rate_limit(&self) -> Option<RateLimitStatus> {
+        let limit = self.header("x-ratelimit-limit")?.parse().ok()?;
+        let remaining = self.header("x-ratelimit-remaining")?.parse().ok()?;
+        let reset = self
+            .header("x-ratelimit-reset")
+            .and_then(|reset| reset.parse().ok())
+            .map(Duration::from_secs);
+        Some(RateLimitStatus {
+            limit,
+            remaining,
+            reset,
+        })
+    }
+
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name)?.to_str().ok()
+    }
+}
+
+impl From<&HttpResponse> for ResponseMeta {
+    fn from(res: &HttpResponse) -> Self {
+        Self {
+            status: res.status(),
+            headers: res.headers().clone(),
+        }
+    }
+}
+
+/// Rate limit quota reported by the api
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitStatus {
+    /// Number of requests allowed in the current window
+    pub limit: u32,
+    /// Number of requests left in the current window
+    pub remaining: u32,
+    /// Time until the window resets, if known
+    pub reset: Option<Duration>,
+}
+
+/// Parse the json body of a response, keeping its metadata
+pub(crate) fn parse_with_meta<T: DeserializeOwned>(
+    res: HttpResponse,
+) -> Result<WithMeta<T>, RequestError> {
+    let meta = ResponseMeta::from(&res);
+    Ok(WithMeta {
+        data: transport::parse(res)?,
+        meta,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_meta_headers() {
+        let res = http::Response::builder()
+            .header("X-Request-Id", "abc123")
+            .header("X-RateLimit-Limit", "1200")
+            .header("X-RateLimit-Remaining", "1199")
+            .header("X-RateLimit-Reset", "42")
+            .body(Vec::new())
+            .unwrap();
+        let meta = ResponseMeta::from(&res);
+        assert_eq!(meta.status(), StatusCode::OK);
+        assert_eq!(meta.request_id(), Some("abc123"));
+        assert_eq!(
+            meta.rate_limit(),
+            Some(RateLimitStatus {
+                limit: 1200,
+                remaining: 1199,
+                reset: Some(Duration::from_secs(42)),
+            })
+        );
+
+        let meta = ResponseMeta::from(&http::Response::new(Vec::new()));
+        assert_eq!(meta.request_id(), None);
+        assert_eq!(meta.rate_limit(), None);
+    }
+}
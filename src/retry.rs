@@ -0,0 +1,102 @@
+use crate::HttpRequest;
+use rand::Rng;
+use std::time::Duration;
+
+/// How failed requests are retried, set with
+/// [`ClientBuilder::retry`](crate::ClientBuilder::retry).
+///
+/// Requests are retried on connection errors, timeouts, rate limiting and server errors, see
+/// [`RequestError::is_retryable`](crate::RequestError::is_retryable). Each retry waits a random
+/// time of up to `initial_backoff * 2^retry`, capped at `max_backoff`.
+///
+/// Requests that would do something twice if sent twice, such as sending chat messages, chat
+/// commands and deleting messages, are only retried when they couldn't connect or were rate
+/// limited, as otherwise Trovo may have handled them already.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(10),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Create a policy that makes at most `max_attempts` attempts at each request, including
+    /// the first.
+    pub fn new(max_attempts: u32) -> Self {
+        Self {
+            max_attempts,
+            ..Self::default()
+        }
+    }
+
+    /// Sets the longest time to wait before the first retry. Defaults to 500ms.
+    pub fn with_initial_backoff(mut self, initial_backoff: Duration) -> Self {
+        self.initial_backoff = initial_backoff;
+        self
+    }
+
+    /// Sets the longest time to wait between any two attempts. Defaults to 10 seconds.
+    pub fn with_max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    /// Maximum number of attempts at each request, including the first
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    /// How long to wait before the given retry, starting from 0
+    pub(crate) fn backoff(&self, retry: u32) -> Duration {
+        let cap = self
+            .initial_backoff
+            .checked_mul(2u32.saturating_pow(retry))
+            .unwrap_or(self.max_backoff)
+            .min(self.max_backoff);
+        cap.mul_f64(rand::thread_rng().gen_range(0.0..=1.0))
+    }
+}
+
+/// Request extension marking requests that change something, such as sending a chat message,
+/// so they're only retried when they can't have reached Trovo
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct NotIdempotent;
+
+/// Copy a request so that it can be sent again
+pub(crate) fn clone_request(request: &HttpRequest) -> HttpRequest {
+    let mut clone = http::Request::new(request.body().clone());
+    *clone.method_mut() = request.method().clone();
+    *clone.uri_mut() = request.uri().clone();
+    *clone.version_mut() = request.version();
+    *clone.headers_mut() = request.headers().clone();
+    if let Some(marker) = request.extensions().get::<NotIdempotent>() {
+        clone.extensions_mut().insert(*marker);
+    }
+    clone
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_is_capped() {
+        let policy = RetryPolicy::new(10)
+            .with_initial_backoff(Duration::from_secs(1))
+            .with_max_backoff(Duration::from_secs(5));
+        for retry in 0..40 {
+            let cap = Duration::from_secs(2u64.saturating_pow(retry).min(5));
+            assert!(policy.backoff(retry) <= cap);
+        }
+    }
+}
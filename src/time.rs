@@ -0,0 +1,42 @@
+//! Timers that work on both native targets, using tokio, and wasm, using the browser's timers.
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use tokio::time::{error::Elapsed, sleep, timeout, Instant};
+
+#[cfg(target_arch = "wasm32")]
+pub(crate) use wasm::*;
+
+#[cfg(target_arch = "wasm32")]
+mod wasm {
+    use futures::future::{select, Either};
+    use send_wrapper::SendWrapper;
+    use std::{convert::TryFrom, future::Future, time::Duration};
+    use thiserror::Error;
+
+    pub(crate) use web_time::Instant;
+
+    /// Error returned by [`timeout`] when the future didn't finish in time
+    #[derive(Debug, Error)]
+    #[error("deadline has elapsed")]
+    pub(crate) struct Elapsed;
+
+    /// Wait for the given duration.
+    ///
+    /// Browser timers aren't `Send`, but wasm is single threaded so they never move threads.
+    pub(crate) fn sleep(duration: Duration) -> SendWrapper<gloo_timers::future::TimeoutFuture> {
+        let millis = u32::try_from(duration.as_millis()).unwrap_or(u32::MAX);
+        SendWrapper::new(gloo_timers::future::TimeoutFuture::new(millis))
+    }
+
+    /// Wait for the future to finish, failing if it takes longer than the given duration
+    pub(crate) async fn timeout<F: Future>(
+        duration: Duration,
+        future: F,
+    ) -> Result<F::Output, Elapsed> {
+        futures::pin_mut!(future);
+        match select(future, sleep(duration)).await {
+            Either::Left((output, _)) => Ok(output),
+            Either::Right(_) => Err(Elapsed),
+        }
+    }
+}
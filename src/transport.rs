@@ -0,0 +1,383 @@
+use crate::{ApiError, RequestError};
+use http::{
+    header::{self, HeaderValue},
+    request::Builder,
+};
+use rand::{distributions::Alphanumeric, Rng};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    convert::TryFrom,
+    error::Error,
+    fmt::{self, Debug, Display},
+    sync::Arc,
+};
+use tracing::Instrument;
+
+/// Header used to send each request's id
+pub(crate) const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// A request to the Trovo api
+pub type HttpRequest = http::Request<Vec<u8>>;
+
+/// A response from the Trovo api
+pub type HttpResponse = http::Response<Vec<u8>>;
+
+/// The http layer used to send requests to the Trovo api.
+///
+/// Implemented for [`reqwest::Client`], which is used by default. Implement this yourself to send
+/// requests through another http client, or to mock the api in tests, and pass it to
+/// [`Client::with_transport`](crate::Client::with_transport).
+#[async_trait::async_trait]
+pub trait HttpTransport: Debug + Send + Sync {
+    /// Send the request and read the response.
+    ///
+    /// Responses with error status codes should be returned as normal rather than as an error,
+    /// so that the api's error body can be read.
+    async fn execute(&self, request: HttpRequest) -> Result<HttpResponse, TransportError>;
+}
+
+#[async_trait::async_trait]
+impl HttpTransport for reqwest::Client {
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn execute(&self, request: HttpRequest) -> Result<HttpResponse, TransportError> {
+        execute_reqwest(self, request).await
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    async fn execute(&self, request: HttpRequest) -> Result<HttpResponse, TransportError> {
+        // Browser fetch futures aren't `Send`, but wasm is single threaded so they never move
+        // threads
+        send_wrapper::SendWrapper::new(execute_reqwest(self, request)).await
+    }
+}
+
+/// Send the request with reqwest, converting between its types and `http`'s
+async fn execute_reqwest(
+    client: &reqwest::Client,
+    request: HttpRequest,
+) -> Result<HttpResponse, TransportError> {
+    let request = reqwest::Request::try_from(request)?;
+    let res = client.execute(request).await?;
+
+    let mut builder = http::Response::builder().status(res.status());
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        builder = builder.version(res.version());
+    }
+    if let Some(headers) = builder.headers_mut() {
+        *headers = res.headers().clone();
+    }
+    // With the compression features enabled reqwest has already decoded the body and
+    // removed the content encoding headers
+    let body = res.bytes().await?.to_vec();
+    builder.body(body).map_err(TransportError::new)
+}
+
+#[async_trait::async_trait]
+impl<T> HttpTransport for Arc<T>
+where
+    T: HttpTransport + ?Sized,
+{
+    async fn execute(&self, request: HttpRequest) -> Result<HttpResponse, TransportError> {
+        (**self).execute(request).await
+    }
+}
+
+/// Error returned by an [`HttpTransport`] when a request couldn't be sent or its response
+/// couldn't be read, such as network errors or timeouts.
+#[derive(Debug)]
+pub struct TransportError {
+    error: Box<dyn Error + Send + Sync>,
+    request_id: Option<String>,
+}
+
+impl TransportError {
+    /// Wrap the given error from the underlying http client
+    pub fn new(error: impl Into<Box<dyn Error + Send + Sync>>) -> Self {
+        Self {
+            error: error.into(),
+            request_id: None,
+        }
+    }
+
+    /// Returns true if the request timed out
+    pub fn is_timeout(&self) -> bool {
+        self.error.is::<crate::time::Elapsed>()
+            || self
+                .error
+                .downcast_ref::<reqwest::Error>()
+                .is_some_and(reqwest::Error::is_timeout)
+    }
+
+    /// Returns true if the connection to the server couldn't be made, so the request wasn't sent
+    pub fn is_connect(&self) -> bool {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.error
+                .downcast_ref::<reqwest::Error>()
+                .is_some_and(reqwest::Error::is_connect)
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            false
+        }
+    }
+
+    /// Returns true if the request wasn't sent because a [`CircuitBreaker`](crate::CircuitBreaker)
+    /// is open
+    pub fn is_circuit_open(&self) -> bool {
+        self.error.is::<crate::CircuitOpen>()
+    }
+
+    /// Id sent with the request in the `X-Request-Id` header, to correlate the error with logs
+    pub fn request_id(&self) -> Option<&str> {
+        self.request_id.as_deref()
+    }
+
+    /// Returns the error from the underlying http client
+    pub fn into_inner(self) -> Box<dyn Error + Send + Sync> {
+        self.error
+    }
+
+    pub(crate) fn with_request_id(mut self, request_id: &str) -> Self {
+        self.request_id = Some(request_id.to_string());
+        self
+    }
+}
+
+impl Display for TransportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.error, f)
+    }
+}
+
+impl Error for TransportError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.error.source()
+    }
+}
+
+impl From<reqwest::Error> for TransportError {
+    fn from(error: reqwest::Error) -> Self {
+        Self::new(error)
+    }
+}
+
+/// Helpers for finishing requests to the Trovo api
+pub(crate) trait BuilderExt {
+    /// Finish the request with the given json body
+    fn json(self, body: &impl Serialize) -> Result<HttpRequest, RequestError>;
+
+    /// Finish the request without a body
+    fn empty(self) -> Result<HttpRequest, RequestError>;
+}
+
+impl BuilderExt for Builder {
+    fn json(self, body: &impl Serialize) -> Result<HttpRequest, RequestError> {
+        let body = serde_json::to_vec(body)?;
+        self.header(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("application/json"),
+        )
+        .body(body)
+        .map_err(|err| RequestError::Transport(TransportError::new(err)))
+    }
+
+    fn empty(self) -> Result<HttpRequest, RequestError> {
+        self.body(Vec::new())
+            .map_err(|err| RequestError::Transport(TransportError::new(err)))
+    }
+}
+
+/// Start building a request to the Trovo api
+pub(crate) fn request(method: http::Method, url: &str, client_id: &str) -> Builder {
+    http::Request::builder()
+        .method(method)
+        .uri(url)
+        .header(header::ACCEPT, HeaderValue::from_static("application/json"))
+        .header("Client-ID", client_id)
+}
+
+/// Get the request's id, generating one and adding it to the `X-Request-Id` header if it
+/// doesn't have one yet
+pub(crate) fn request_id(request: &mut HttpRequest) -> String {
+    if let Some(id) = request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|id| id.to_str().ok())
+    {
+        return id.to_string();
+    }
+    let id: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(16)
+        .map(char::from)
+        .collect();
+    if let Ok(value) = HeaderValue::from_str(&id) {
+        request.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    id
+}
+
+/// Send the request, turning error responses into [`RequestError`]s
+pub(crate) async fn send(
+    transport: &dyn HttpTransport,
+    mut request: HttpRequest,
+) -> Result<HttpResponse, RequestError> {
+    let request_id = request_id(&mut request);
+    let span = debug_span!(
+        "trovo_request",
+        request_id = %request_id,
+        method = %request.method(),
+        path = request.uri().path(),
+    );
+    async {
+        let res = transport
+            .execute(request)
+            .await
+            .map_err(|err| err.with_request_id(&request_id))?;
+        let status = res.status();
+
+        let api_error = if ApiError::can_handle_code(status) {
+            serde_json::from_slice::<ApiError>(res.body()).ok()
+        } else {
+            None
+        };
+        let res = if let Some(mut err) = api_error {
+            err.request_id = Some(request_id.clone());
+            err.http_status = Some(status);
+            Err(RequestError::ApiError(err))
+        } else if !status.is_success() {
+            Err(RequestError::Status(status))
+        } else {
+            Ok(res)
+        };
+        if let Err(err) = &res {
+            debug!(%err, %status, "request failed");
+        }
+        res
+    }
+    .instrument(span)
+    .await
+}
+
+/// Parse the json body of a response
+pub(crate) fn parse<T: DeserializeOwned>(res: HttpResponse) -> Result<T, RequestError> {
+    Ok(serde_json::from_slice(res.body())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ErrorStatus;
+    use http::StatusCode;
+
+    #[derive(Debug)]
+    struct MockTransport(StatusCode, &'static str);
+
+    #[async_trait::async_trait]
+    impl HttpTransport for MockTransport {
+        async fn execute(&self, _request: HttpRequest) -> Result<HttpResponse, TransportError> {
+            Ok(http::Response::builder()
+                .status(self.0)
+                .body(self.1.as_bytes().to_vec())
+                .unwrap())
+        }
+    }
+
+    fn test_request() -> HttpRequest {
+        request(http::Method::GET, "https://example.com/test", "client id")
+            .empty()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn send_maps_error_responses() {
+        let transport = MockTransport(
+            StatusCode::BAD_REQUEST,
+            r#"{"status": 11714, "message": "access token has expired"}"#,
+        );
+        match send(&transport, test_request()).await {
+            Err(RequestError::ApiError(err)) => {
+                assert_eq!(err.status, ErrorStatus::AccessTokenExpired);
+                assert!(err.request_id.is_some());
+            }
+            res => panic!("unexpected result {:?}", res),
+        }
+
+        let transport = MockTransport(StatusCode::NOT_FOUND, "");
+        assert!(matches!(
+            send(&transport, test_request()).await,
+            Err(RequestError::Status(StatusCode::NOT_FOUND))
+        ));
+
+        // Bodies that aren't trovo errors keep the status code
+        let transport = MockTransport(StatusCode::INTERNAL_SERVER_ERROR, "Internal Server Error");
+        match send(&transport, test_request()).await {
+            Err(err @ RequestError::Status(StatusCode::INTERNAL_SERVER_ERROR)) => {
+                assert!(err.is_retryable())
+            }
+            res => panic!("unexpected result {:?}", res),
+        }
+
+        let transport = MockTransport(StatusCode::OK, r#"{"value": 1}"#);
+        let res = send(&transport, test_request()).await.unwrap();
+        let body: serde_json::Value = parse(res).unwrap();
+        assert_eq!(body["value"], 1);
+    }
+
+    #[test]
+    fn request_ids_are_kept() {
+        let mut request = test_request();
+        let id = request_id(&mut request);
+        assert_eq!(id.len(), 16);
+        assert_eq!(request.headers()[REQUEST_ID_HEADER], id.as_str());
+        assert_eq!(request_id(&mut request), id);
+    }
+
+    #[cfg(feature = "gzip")]
+    #[tokio::test]
+    async fn reqwest_decodes_gzip_responses() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        // gzip encoded `{"value": 1}`
+        const BODY: [u8; 32] = [
+            0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x03, 0xab, 0x56, 0x2a, 0x4b,
+            0xcc, 0x29, 0x4d, 0x55, 0xb2, 0x52, 0x30, 0xac, 0x05, 0x00, 0x58, 0x49, 0xac, 0xd5,
+            0x0c, 0x00, 0x00, 0x00,
+        ];
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            while !request.ends_with(b"\r\n\r\n") {
+                request.push(stream.read_u8().await.unwrap());
+            }
+            let headers = String::from_utf8(request).unwrap().to_lowercase();
+            assert!(headers.contains("accept-encoding: gzip"));
+
+            let head = format!(
+                "HTTP/1.1 200 OK\r\nContent-Encoding: gzip\r\nContent-Length: {}\r\n\r\n",
+                BODY.len()
+            );
+            stream.write_all(head.as_bytes()).await.unwrap();
+            stream.write_all(&BODY).await.unwrap();
+        });
+
+        let request = request(
+            http::Method::GET,
+            &format!("http://{}/test", addr),
+            "client id",
+        )
+        .empty()
+        .unwrap();
+        let client = reqwest::Client::builder().no_proxy().build().unwrap();
+        let res = send(&client, request).await.unwrap();
+        assert!(res.headers().get(header::CONTENT_ENCODING).is_none());
+        let body: serde_json::Value = parse(res).unwrap();
+        assert_eq!(body["value"], 1);
+        server.await.unwrap();
+    }
+}